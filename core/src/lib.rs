@@ -0,0 +1,2066 @@
+//! Parsing and code generation behind the `#[platform_spi]` attribute, as a library for 
+//! other procedural macros to reuse. The contract syntax and arguments are described on 
+//! the attribute itself, in the platform-spi crate.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{bracketed, ext::IdentExt, parenthesized, parse::Parse, punctuated::Punctuated, spanned::Spanned, token::{Comma, Eq, Semi}, Token};
+
+pub mod scaffold;
+
+/// Expands the function-like form, in which the attribute's arguments are followed by `;` 
+/// and the inline module, e.g. `targets = [macos, linux]; mod platform { ... }`.
+pub fn expand_inline(input: TokenStream2, source_dir: Option<PathBuf>) -> TokenStream2 {
+    let mut tokens = input.into_iter();
+    let args: TokenStream2 = tokens.by_ref()
+        .take_while(|token| !matches!(token, proc_macro2::TokenTree::Punct(punct) if punct.as_char() == ';'))
+        .collect();
+    let item: TokenStream2 = tokens.collect();
+    if item.is_empty() {
+        let span = args.clone().into_iter().last().map_or_else(proc_macro2::Span::call_site, |token| token.span());
+        return syn::Error::new(span, "Expected the arguments followed by ';' and the module, e.g. 'targets = [linux]; mod platform { ... }'").to_compile_error()
+    }
+    expand(args, item, source_dir)
+}
+
+/// Expands the `#[platform_spi(...)]` attribute, given its arguments and the inline module 
+/// it is applied to. Source paths are resolved from `source_dir`, the directory of the 
+/// invoking source file, where known.
+pub fn expand(args: TokenStream2, item: TokenStream2, source_dir: Option<PathBuf>) -> TokenStream2 {
+
+    // the attribute and the module are checked independently, so that mistakes in both 
+    // are reported together.
+    let config = syn::parse2::<SpiAttributes>(args).map_err(|error| error.to_compile_error());
+    let mut mod_decl = match syn::parse2::<syn::ItemMod>(item) {
+        Ok(mod_decl) => mod_decl,
+        Err(error) => return error.to_compile_error()
+    };
+    // with `allow_shared_items = true`, items that are not contract markers are emitted 
+    // into the parent module as written, rather than rejected. Should the attribute not 
+    // parse they are set aside too, so that only mistakes in the contract are reported.
+    let shared_items: Vec<syn::Item> = match (&config, &mut mod_decl.content) {
+        (Ok(SpiAttributes { allow_shared_items: true, .. }) | Err(_), Some((_, content))) => {
+            let (shared, contract) = std::mem::take(content).into_iter().partition(is_shared_item);
+            *content = contract;
+            shared
+        },
+        _ => vec![]
+    };
+    let rewritten_decl = SpiModule::try_from(&mod_decl);
+
+    let (mut config, rewritten_decl) = match (config, rewritten_decl) {
+        (Ok(config), Ok(module)) => (config, module),
+        (config, module) => {
+            return config.err().into_iter().chain(module.err()).collect()
+        }
+    };
+    config.set_source_dir(source_dir);
+
+    // the inline module declaration, rewritten as module file import.
+    let mod_import = &rewritten_decl.mod_import_decl;
+
+    let target_cfgs: Vec<TokenStream2> = config.target_cfgs();
+    let fallback_cfgs: Vec<TokenStream2> = config.fallback_cfgs();
+    let mod_paths: Vec<syn::LitStr> = config.source_paths();
+    if config.require_all_targets {
+        if let Err(missing) = config.check_source_files_exist() {
+            return missing
+        }
+    }
+
+    // SPI type aliases hoisted from the module declaration.
+    let aliases: Vec<syn::Item> = match config.doc_cfg {
+        true => rewritten_decl.aliases.iter().cloned().map(annotate_doc_cfg).collect(),
+        false => rewritten_decl.aliases.clone()
+    };
+
+    // static assertions checking the contract's trait bounds.
+    let assertions = &rewritten_decl.assertions;
+    let send_sync_assertions: Vec<SpiAssertion> = config.send_sync_assertions().into_iter()
+        .map(|assertion| gate_assertion(assertion, &mod_import.attrs, &aliases))
+        .collect();
+    let named_assertions = named_assertions(assertions.iter().chain(&send_sync_assertions));
+
+    let platform_ident = &mod_import.ident;
+    let module_cfgs = cfg_attrs(&mod_import.attrs);
+    let shared_items: Vec<TokenStream2> = shared_items.iter().map(|item| quote! {
+        #(#module_cfgs)*
+        #item
+    }).collect();
+    let reexport_all = config.reexport_all.then(|| {
+        let reexport: syn::Item = syn::parse_quote! {
+            #(#module_cfgs)*
+            pub use #platform_ident::*;
+        };
+        match config.doc_cfg {
+            true => annotate_doc_cfg(reexport),
+            false => reexport
+        }
+    });
+
+    let factory = config.factory.as_ref().map(|FactorySpec { name, ty, constructor }| {
+        let doc = format!(" Constructs the platform's `{}`.", ty.to_token_stream());
+        let construct = match constructor.to_string().as_str() {
+            "default" => quote! { <#ty as ::core::default::Default>::default() },
+            _ => quote! { <#ty>::#constructor() }
+        };
+        quote! {
+            #(#module_cfgs)*
+            #[doc = #doc]
+            pub fn #name() -> #ty {
+                #construct
+            }
+        }
+    });
+
+    let default_constructors = match config.default_constructors {
+        true => assertions.iter().filter_map(default_constructor).collect(),
+        false => vec![]
+    };
+
+    // the module's cfgs gate the generated items too, spelled out once to be repeated 
+    // alongside the cfg of each target.
+    let module_cfg_attrs = quote! { #(#module_cfgs)* };
+    let supported_targets = config.emit_target_list.then(|| {
+        let target_names = config.target_names();
+        quote! {
+            #module_cfg_attrs
+            /// Targets with a platform implementation, by cfg value.
+            pub const SUPPORTED_TARGETS: &[&str] = &[#(#target_names),*];
+        }
+    });
+
+    let current_target = config.emit_current_target.then(|| {
+        let (target_cfgs, target_names) = config.current_target_cfgs();
+        quote! {
+            #(
+                #module_cfg_attrs
+                /// Name of the target the platform implementation was selected for.
+                #[cfg(#target_cfgs)]
+                pub fn current_target() -> &'static str {
+                    #target_names
+                }
+            )*
+
+            #module_cfg_attrs
+            /// Name of the target the platform implementation was selected for.
+            #[cfg(not(any(#( #fallback_cfgs ),*)))]
+            pub fn current_target() -> &'static str {
+                "unknown"
+            }
+        }
+    });
+
+    let platform_enum = config.emit_platform_enum.then(|| {
+        let target_names = config.target_names();
+        let variants = target_names.iter().map(|name| platform_variant(name));
+        let variant_docs = target_names.iter().map(|name| format!(" The \"{name}\" target."));
+        let (target_cfgs, target_names) = config.current_target_cfgs();
+        let selected_variants = target_names.iter().map(|name| platform_variant(name));
+        quote! {
+            #module_cfg_attrs
+            /// Targets with a platform implementation.
+            #[derive(::core::clone::Clone, ::core::marker::Copy, ::core::fmt::Debug, ::core::cmp::PartialEq, ::core::cmp::Eq)]
+            pub enum Platform {
+                #(
+                    #[doc = #variant_docs]
+                    #variants,
+                )*
+                /// A platform matching none of the targets.
+                Unsupported
+            }
+
+            #(
+                #module_cfg_attrs
+                /// The platform being built for.
+                #[cfg(#target_cfgs)]
+                pub const PLATFORM: Platform = Platform::#selected_variants;
+            )*
+
+            #module_cfg_attrs
+            /// The platform being built for.
+            #[cfg(not(any(#( #fallback_cfgs ),*)))]
+            pub const PLATFORM: Platform = Platform::Unsupported;
+        }
+    });
+
+    let unsupported_path = config.unsupported_source_path();
+    let platform_module = match (config.cfg_if_backend, config.targets.is_empty()) {
+        // a single implementation is imported everywhere, and only checked against the contract.
+        _ if config.single.is_some() => {
+            let single_path = config.single_source_path();
+            quote! {
+                #[path = #single_path]
+                #mod_import
+            }
+        },
+        // arms of cfg_if! are tried in order, so the targets need no exclusions of their own.
+        (true, false) => {
+            let selections = config.targets.iter().map(|target| config.selection_cfg(target));
+            let doc_arm = config.doc_target_index().map(|index| {
+                let doc_path = &mod_paths[index];
+                quote! {
+                    if #[cfg(doc)] {
+                        #[path = #doc_path]
+                        #mod_import
+                    } else
+                }
+            });
+            let unsupported_arm = match (config.unsupported, &config.unsupported_error) {
+                (_, Some(message)) => Some(quote! {
+                    else {
+                        #(#module_cfgs)*
+                        ::core::compile_error!(#message);
+                    }
+                }),
+                (true, None) => Some(quote! {
+                    else {
+                        #[path = #unsupported_path]
+                        #mod_import
+                    }
+                }),
+                (false, None) => None
+            };
+            quote! {
+                ::cfg_if::cfg_if! {
+                    #doc_arm
+                    #(
+                        if #[cfg(#selections)] {
+                            #[path = #mod_paths]
+                            #mod_import
+                        }
+                    )else*
+                    #unsupported_arm
+                }
+            }
+        },
+        // the module is declared once, with the path of whichever target is selected. The 
+        // target cfgs never overlap, so at most one path applies.
+        _ => {
+            let unsupported_selection = match config.unsupported {
+                true => quote! {
+                    #[cfg_attr(not(any(#( #fallback_cfgs ),*)), path = #unsupported_path)]
+                },
+                false => quote! {
+                    #[cfg(any(#( #target_cfgs ),*))]
+                }
+            };
+            let unsupported_error = config.unsupported_error.as_ref().map(|message| quote! {
+                #(#module_cfgs)*
+                #[cfg(not(any(#( #fallback_cfgs ),*)))]
+                ::core::compile_error!(#message);
+            });
+            quote! {
+                #( #[cfg_attr(#target_cfgs, path = #mod_paths)] )*
+                #unsupported_selection
+                #mod_import
+
+                #unsupported_error
+            }
+        }
+    };
+
+    quote! {
+        #platform_module
+
+        #reexport_all
+
+        #factory
+
+        #(#default_constructors)*
+
+        #supported_targets
+
+        #current_target
+
+        #platform_enum
+
+        #(#aliases)*
+
+        #(#shared_items)*
+
+        // scoped so that no names used by the assertions escape into the parent module.
+        const _: () = {
+            #(#named_assertions)*
+        };
+    }
+
+}
+
+// a constructor for an alias asserted to implement Default, gated like its assertion, e.g. 
+// `new_platform_service` for `impl Default for PlatformService {}`
+fn default_constructor(assertion: &SpiAssertion) -> Option<TokenStream2> {
+    // an assertion is gated once by the module's cfgs and again by those of its alias.
+    let mut cfgs: Vec<&syn::Attribute> = vec![];
+    let mut assertion = assertion;
+    while let SpiAssertion::Gated(gate, inner) = assertion {
+        cfgs.extend(gate);
+        assertion = inner;
+    }
+    let SpiAssertion::ImplAll(ty, traits) = assertion else {
+        return None
+    };
+    let alias = match ty {
+        syn::Type::Path(type_path) if type_path.qself.is_none() => type_path.path.get_ident()?,
+        _ => return None
+    };
+    let is_default = |bound: &syn::TypeParamBound| matches!(
+        bound, syn::TypeParamBound::Trait(bound) if bound.path.segments.last().is_some_and(|segment| segment.ident == "Default")
+    );
+    if !traits.iter().any(is_default) {
+        return None
+    }
+
+    let mut name = String::from("new");
+    for (index, ch) in alias.unraw().to_string().chars().enumerate() {
+        if index == 0 || ch.is_uppercase() {
+            name.push('_');
+        }
+        name.extend(ch.to_lowercase());
+    }
+    let name = syn::Ident::new(&name, alias.span());
+    let doc = format!(" Constructs the platform's default `{alias}`.");
+    Some(quote! {
+        #(#cfgs)*
+        #[doc = #doc]
+        pub fn #name() -> #alias {
+            <#alias as ::core::default::Default>::default()
+        }
+    })
+}
+
+// upper camel case variant for a cfg value, e.g. "Macos" for "macos" and "X8664" for "x86_64".
+// Numeric values such as pointer widths are prefixed to form an identifier, e.g. "Target64".
+fn platform_variant(name: &str) -> syn::Ident {
+    let variant: String = name.split('_').map(|word| {
+        let mut chars = word.chars();
+        chars.next().map_or_else(String::new, |first| first.to_uppercase().chain(chars).collect())
+    }).collect();
+    let variant = match variant.starts_with(|first: char| first.is_ascii_digit()) {
+        true => format!("Target{variant}"),
+        false => variant
+    };
+    syn::Ident::new(&variant, proc_macro2::Span::call_site())
+}
+
+/// attribute names that list target platforms, and the cfg key each one selects on.
+const TARGET_KEYS: &[(&str, &str)] = &[
+    ("targets", "target_os"),
+    ("arch_targets", "target_arch"),
+    ("family_targets", "target_family"),
+    ("vendor_targets", "target_vendor"),
+    ("env_targets", "target_env"),
+    ("pointer_width_targets", "target_pointer_width"),
+    ("endian_targets", "target_endian"),
+    ("feature_targets", "feature"),
+];
+
+/// target_os values of the targets rustc supports, for checking with `strict_targets = true`.
+const KNOWN_TARGET_OS: &[&str] = &[
+    "aix", "amdhsa", "android", "cuda", "cygwin", "dragonfly", "emscripten", "espidf", 
+    "freebsd", "fuchsia", "haiku", "helenos", "hermit", "horizon", "hurd", "illumos", "ios", 
+    "l4re", "linux", "lynxos178", "macos", "managarm", "motor", "netbsd", "none", "nto", 
+    "nuttx", "openbsd", "psp", "psx", "qurt", "redox", "rtems", "solaris", "solid_asp3", 
+    "teeos", "trusty", "tvos", "uefi", "unknown", "vexos", "visionos", "vita", "vxworks", 
+    "wasi", "watchos", "windows", "xous", "zkvm",
+];
+
+/// keys of a matrix target, e.g. `{ os = linux, arch = arm }`, and the cfg key each selects on.
+const MATRIX_KEYS: &[(&str, &str)] = &[
+    ("os", "target_os"),
+    ("arch", "target_arch"),
+    ("family", "target_family"),
+    ("vendor", "target_vendor"),
+    ("env", "target_env"),
+    ("pointer_width", "target_pointer_width"),
+    ("endian", "target_endian"),
+    ("feature", "feature"),
+];
+
+/// The arguments of the `#[platform_spi(...)]` attribute, parsed from its tokens.
+pub struct SpiAttributes {
+    targets: Punctuated::<SpiTarget, Comma>,
+    module_path: syn::LitStr,
+    /// whether unsupported.rs is imported on platforms matching none of the targets
+    unsupported: bool,
+    /// source file imported in place of unsupported.rs, relative to module_path
+    unsupported_path: Option<syn::LitStr>,
+    /// message of a compile_error! emitted in place of unsupported.rs
+    unsupported_error: Option<syn::LitStr>,
+    /// name of each source file, with `{target}` in place of the target's file stem
+    file_pattern: Option<syn::LitStr>,
+    /// extension of each source file not named by file_pattern, "rs" by default
+    extension: syn::LitStr,
+    /// aliases that must implement both Send and Sync
+    require_send_sync: Punctuated::<syn::Type, Comma>,
+    /// named sets of cfg values, each selecting a single source file when listed as a target,
+    /// from `groups = { unix = [linux, macos] }`
+    groups: Vec<(TargetName, Punctuated::<TargetName, Comma>)>,
+    /// whether the platform module is selected by a cfg_if! block, from `backend = cfg_if`
+    cfg_if_backend: bool,
+    /// whether every public item of the platform module is re-exported by a glob import
+    reexport_all: bool,
+    /// function constructing a contract type, from `factory = "backend: PlatformService = default"`
+    factory: Option<FactorySpec>,
+    /// whether aliases with an `impl Default` declaration get a generated constructor
+    default_constructors: bool,
+    /// whether the SUPPORTED_TARGETS const is generated
+    emit_target_list: bool,
+    /// whether the current_target function is generated
+    emit_current_target: bool,
+    /// whether the Platform enum and PLATFORM const are generated
+    emit_platform_enum: bool,
+    /// whether hoisted items gated by #[cfg] are annotated with #[doc(cfg)]
+    doc_cfg: bool,
+    /// target selected whenever rustdoc builds documentation, from `doc_target = linux`
+    doc_target: Option<TargetName>,
+    /// whether items other than contract markers are emitted unchanged into the parent module
+    allow_shared_items: bool,
+    /// whether unknown target_os values are errors, from `strict_targets = true`
+    strict_targets: bool,
+    /// the only source file, imported on every platform, from `single = "impl.rs"`
+    single: Option<syn::LitStr>,
+    /// whether each named source file must exist, from `require_all_targets = true`
+    require_all_targets: bool,
+    /// directory of the source file invoking the macro, when the compiler provides it
+    source_dir: Option<PathBuf>
+}
+impl SpiAttributes {
+    /// Resolves source paths from `dir`, the directory of the invoking source file, rather 
+    /// than from the directory of the enclosing module as `#[path]` attributes are.
+    pub fn set_source_dir(&mut self, dir: Option<PathBuf>) {
+        self.source_dir = dir;
+    }
+
+    /// String literals naming each module source file, e.g. "macos.rs", spanned at the 
+    /// target that names the file so that errors and navigation lead back to it.
+    pub fn source_paths(&self) -> Vec<syn::LitStr> {
+        self.targets.iter().map(|target| match &target.path {
+            Some(path) => (self.module_file_path(&path.value()), path.span()),
+            None => (self.module_file_path(&self.file_name(&target.file_stem())), target.name.span())
+        }).map(|(path, span)| syn::LitStr::new(&self.resolve_source_path(path), span)).collect()
+    }
+
+    // source file name for a file stem, e.g. "macos.rs", "macos.gen.rs" with 
+    // `extension = "gen.rs"`, or "macos_impl.rs" following `file_pattern = "{target}_impl.rs"`.
+    fn file_name(&self, stem: &str) -> String {
+        match &self.file_pattern {
+            Some(pattern) => pattern.value().replace("{target}", stem),
+            None => format!("{stem}.{}", self.extension.value())
+        }
+    }
+
+    // errors for each target source file, or explicitly named fallback file, missing from 
+    // disk. Paths are only checked once resolved from the invoking file's directory.
+    fn check_source_files_exist(&self) -> Result<(), TokenStream2> {
+        if self.source_dir.is_none() {
+            return Ok(())
+        }
+        let fallback = self.unsupported_path.as_ref().map(|_| self.unsupported_source_path());
+        let missing: TokenStream2 = self.source_paths().into_iter().chain(fallback).chain(self.single_source_path())
+            .filter(|path| !Path::new(&path.value()).is_file())
+            .map(|path| syn::Error::new(path.span(), format!("Source file \"{}\" does not exist", path.value())).to_compile_error())
+            .collect();
+        match missing.is_empty() {
+            true => Ok(()),
+            false => Err(missing)
+        }
+    }
+
+    // #[path] is resolved relative to the directory of the enclosing module, which for a 
+    // module nested inline in its file is not the file's own directory. Where the invoking 
+    // file is known its directory is prepended, so that paths always resolve from the file.
+    fn resolve_source_path(&self, path: String) -> String {
+        match &self.source_dir {
+            Some(dir) => dir.join(&path).to_string_lossy().into_owned(),
+            None => path
+        }
+    }
+
+    // joins module_path and a file name with single separators and without "." segments, 
+    // e.g. "dir/macos.rs" for "dir", "dir/", and "./dir", or "macos.rs" for ".". Forward 
+    // slashes are accepted by #[path] on every host.
+    fn module_file_path(&self, file: &str) -> String {
+        let module_path = self.module_path.value();
+        let root = if module_path.starts_with('/') { "/" } else { "" };
+        let segments: Vec<&str> = module_path.split('/').chain(file.split('/'))
+            .filter(|segment| !segment.is_empty() && *segment != ".")
+            .collect();
+        format!("{root}{}", segments.join("/"))
+    }
+
+    // cfg values selecting any target, in the order listed, e.g. "macos" and "linux" 
+    // for `unix = [macos, linux]`. Qualified targets repeat a value, so each is listed once.
+    fn target_names(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.targets.iter()
+            .flat_map(|target| target.values.iter().map(|value| value.to_string()))
+            .filter(|value| seen.insert(value.clone()))
+            .collect()
+    }
+
+    // cfg predicates selecting each cfg value of each target, paired with the value. A 
+    // target covering several values is narrowed to each of them in turn.
+    fn current_target_cfgs(&self) -> (Vec<TokenStream2>, Vec<String>) {
+        self.targets.iter().zip(self.target_cfgs()).flat_map(|(target, target_cfg)| {
+            let single_value = target.values.len() == 1;
+            target.values.iter().map(move |value| match single_value {
+                true => (target_cfg.clone(), value.to_string()),
+                false => {
+                    let value_cfg = target.value_cfg(value);
+                    (quote! { all(#target_cfg, #value_cfg) }, value.to_string())
+                }
+            }).collect::<Vec<_>>()
+        }).unzip()
+    }
+
+    // a target listed by a group's name, without values of its own, is selected by the 
+    // group's members.
+    fn resolve_groups(&mut self) {
+        for target in self.targets.iter_mut() {
+            let selected_by_name = matches!(
+                target.values.first(), Some(value) if target.values.len() == 1 && value.name == target.name.name
+            );
+            let group = self.groups.iter().find(|(group_name, _)| group_name.name == target.name.name);
+            if let (true, Some((_, members))) = (selected_by_name, group) {
+                target.values = members.clone();
+            }
+        }
+    }
+
+    fn send_sync_assertions(&self) -> Vec<SpiAssertion> {
+        self.require_send_sync.iter().map(
+            |ty| SpiAssertion::ImplAll(ty.clone(), vec![syn::parse_quote!(::core::marker::Send), syn::parse_quote!(::core::marker::Sync)])
+        ).collect()
+    }
+
+    // string literal naming the only module source file, from `single = "impl.rs"`
+    fn single_source_path(&self) -> Option<syn::LitStr> {
+        let single = self.single.as_ref()?;
+        Some(syn::LitStr::new(&self.resolve_source_path(self.module_file_path(&single.value())), single.span()))
+    }
+
+    /// String literal naming the unsupported module source file, e.g. "unsupported.rs"
+    pub fn unsupported_source_path(&self) -> syn::LitStr {
+        let (path, span) = match &self.unsupported_path {
+            Some(path) => (self.module_file_path(&path.value()), path.span()),
+            None => (self.module_file_path(&self.file_name("unsupported")), proc_macro2::Span::call_site())
+        };
+        syn::LitStr::new(&self.resolve_source_path(path), span)
+    }
+
+    /// cfg predicates selecting each target, e.g. `target_os = "macos"`, or 
+    /// `any(target_os = "macos", target_os = "linux")` for a target covering several values.
+    /// Where targets may overlap, each also excludes the targets listed before it.
+    /// With a doc_target, it is also selected by `doc`, and each other target only without it.
+    pub fn target_cfgs(&self) -> Vec<TokenStream2> {
+        let selections: Vec<TokenStream2> = self.targets.iter().map(|target| self.selection_cfg(target)).collect();
+        let selections: Vec<TokenStream2> = match self.targets_may_overlap() {
+            false => selections,
+            true => selections.iter().enumerate().map(|(index, selection)| match &selections[..index] {
+                [] => selection.clone(),
+                earlier => quote! { all(#selection, not(any(#(#earlier),*))) }
+            }).collect()
+        };
+
+        match self.doc_target_index() {
+            None => selections,
+            Some(doc_index) => selections.into_iter().enumerate().map(|(index, selection)| match index == doc_index {
+                true => quote! { any(doc, #selection) },
+                false => quote! { all(not(doc), #selection) }
+            }).collect()
+        }
+    }
+
+    /// cfg predicates which together select any target, e.g. `target_os = "macos"`, 
+    /// and `doc` where a doc_target is named.
+    pub fn fallback_cfgs(&self) -> Vec<TokenStream2> {
+        let doc = self.doc_target.as_ref().map(|_| quote! { doc });
+        self.targets.iter().flat_map(|target| match target.qualifiers.is_empty() && target.predicate.is_none() {
+            true => target.values.iter().map(|value| target.value_cfg(value)).collect(),
+            false => vec![self.selection_cfg(target)]
+        }).chain(doc).collect()
+    }
+
+    fn doc_target_index(&self) -> Option<usize> {
+        let doc_target = self.doc_target.as_ref()?;
+        self.targets.iter().position(|target| target.name.name == doc_target.name)
+    }
+
+    fn selection_cfg(&self, target: &SpiTarget) -> TokenStream2 {
+        if let Some(predicate) = &target.predicate {
+            return predicate.clone()
+        }
+        let values: Vec<TokenStream2> = target.values.iter().map(|value| target.value_cfg(value)).collect();
+        let selection = match values.as_slice() {
+            [value] => value.clone(),
+            _ => quote! { any(#(#values),*) }
+        };
+        match target.qualifiers.as_slice() {
+            [] => selection,
+            qualifiers => {
+                let qualifiers = qualifiers.iter().map(|(key, value)| quote! { #key = #value });
+                quote! { all(#selection, #(#qualifiers),*) }
+            }
+        }
+    }
+
+    // several features can be enabled at once, a qualified target such as 
+    // `linux(feature = epoll)` overlaps with a plain `linux`, and targets selected on 
+    // different cfg keys, such as an OS and an architecture, can both hold, as can a 
+    // predicate written out in full alongside any other target.
+    fn targets_may_overlap(&self) -> bool {
+        let mut cfg_keys = self.targets.iter().map(|target| target.cfg_key.to_string());
+        let first_key = cfg_keys.next();
+        self.targets.iter().any(|target| target.cfg_key == "feature" || !target.qualifiers.is_empty() || target.predicate.is_some())
+            || cfg_keys.any(|key| Some(key) != first_key)
+    }
+}
+
+// an error for each target_os value rustc doesn't know, suggesting the closest known value, 
+// e.g. "windows" for "windwos". Values qualifying a target, e.g. the `os` of 
+// `{ arch = x86_64, os = linux }`, are checked too.
+fn check_known_target_os(targets: &Punctuated<SpiTarget, Comma>) -> syn::Result<()> {
+    let mut errors: Option<syn::Error> = None;
+    let values = targets.iter()
+        .filter(|target| target.cfg_key == "target_os" && target.predicate.is_none())
+        .flat_map(|target| target.values.iter().map(|value| (value.name.clone(), value.span())));
+    let qualifiers = targets.iter()
+        .flat_map(|target| target.qualifiers.iter())
+        .filter(|(key, _)| key == "target_os")
+        .map(|(_, value)| (value.value(), value.span()));
+    for (value, span) in values.chain(qualifiers).filter(|(value, _)| !KNOWN_TARGET_OS.contains(&value.as_str())) {
+        let closest = KNOWN_TARGET_OS.iter()
+            .map(|known| (edit_distance(&value, known), known))
+            .min()
+            .filter(|(distance, _)| *distance <= 2);
+        let message = match closest {
+            Some((_, known)) => format!("Unknown target_os '{value}', did you mean '{known}'?"),
+            None => format!("Unknown target_os '{value}'")
+        };
+        let error = syn::Error::new(span, message);
+        match &mut errors {
+            Some(errors) => errors.combine(error),
+            None => errors = Some(error)
+        }
+    }
+    errors.map_or(Ok(()), Err)
+}
+
+// the number of single character insertions, deletions, and substitutions between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+// duplicate targets would expand to colliding module declarations, so report them 
+// here at the repeated name rather than at the generated modules.
+fn check_duplicate_targets(targets: &Punctuated<SpiTarget, Comma>) -> syn::Result<()> {
+    let mut names = HashSet::new();
+    let mut values = HashSet::new();
+
+    for target in targets {
+        if !names.insert(target.file_stem()) {
+            return Err(syn::Error::new(target.name.span(), format!("Target '{}' is listed twice", target.file_stem())))
+        }
+        let qualifiers = target.qualifier_suffix();
+        for value in &target.values {
+            if !values.insert(format!("{}={value}{qualifiers}", target.cfg_key)) {
+                return Err(syn::Error::new(value.span(), format!("Target '{value}' is listed twice")))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A target name or cfg value, written as an identifier such as `linux`, or as an 
+/// integer literal for numeric cfg values such as the `64` of `target_pointer_width`.
+#[derive(Clone)]
+struct TargetName {
+    name: String,
+    span: proc_macro2::Span
+}
+impl TargetName {
+    fn span(&self) -> proc_macro2::Span {
+        self.span
+    }
+}
+impl std::fmt::Display for TargetName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+impl Parse for TargetName {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::LitInt) {
+            let value: syn::LitInt = input.parse()?;
+            Ok(TargetName { name: value.base10_digits().to_string(), span: value.span() })
+        } else {
+            // a raw identifier such as `r#move` names the file "move.rs" and the cfg value "move".
+            let name = syn::Ident::parse(input)?;
+            Ok(TargetName { name: name.unraw().to_string(), span: name.span() })
+        }
+    }
+}
+
+/// A generated constructor function, e.g. `backend: PlatformService = default`.
+struct FactorySpec {
+    name: syn::Ident,
+    ty: syn::Type,
+    /// `default` for `Default::default()`, otherwise an associated function of the type
+    constructor: syn::Ident
+}
+impl Parse for FactorySpec {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let _colon: Token![:] = input.parse()?;
+        let ty = input.parse()?;
+        let _eq: Eq = input.parse()?;
+        let constructor = input.parse()?;
+        Ok(FactorySpec { name, ty, constructor })
+    }
+}
+
+/// A platform source file, and the cfg values that select it.
+struct SpiTarget {
+    /// file stem of the target source file, e.g. "unix" for "unix.rs"
+    name: TargetName,
+    /// cfg key the target's values are matched against, e.g. "target_os"
+    cfg_key: syn::Ident,
+    /// cfg values selecting this target, e.g. `macos` and `linux`. A target 
+    /// written without a value list is selected by its own name.
+    values: Punctuated::<TargetName, Comma>,
+    /// source file overriding "{name}.rs", relative to module_path
+    path: Option<syn::LitStr>,
+    /// further cfg options that must also be set to select this target, e.g. 
+    /// `feature = "io_uring"` for `linux(feature = io_uring)`
+    qualifiers: Vec<(syn::Ident, syn::LitStr)>,
+    /// cfg predicate selecting the target in place of its values, e.g. 
+    /// `not(target_os = "windows")` for `posix = not(target_os = "windows")`
+    predicate: Option<TokenStream2>,
+    /// whether the target was written as a matrix entry, e.g. `{ os = linux, arch = arm }`,
+    /// which names its own cfg key rather than taking that of its argument
+    matrix: bool
+}
+impl SpiTarget {
+    /// file stem of the target source file, with any qualifier values appended, 
+    /// e.g. "linux_io_uring" for `linux(feature = io_uring)`
+    fn file_stem(&self) -> String {
+        format!("{}{}", self.name, self.qualifier_suffix())
+    }
+
+    fn value_cfg(&self, value: &TargetName) -> TokenStream2 {
+        let cfg_key = &self.cfg_key;
+        let value = value.to_string();
+        quote! { #cfg_key = #value }
+    }
+
+    fn qualifier_suffix(&self) -> String {
+        self.qualifiers.iter().map(|(_, value)| format!("_{}", value.value())).collect()
+    }
+
+    // a matrix entry, e.g. `{ os = linux, arch = arm } => "linux_arm.rs"`.
+    fn parse_matrix(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let entries;
+        let brace = syn::braced!(entries in input);
+        let mut keys = vec![];
+        while !entries.is_empty() {
+            let key = syn::Ident::parse(&entries)?;
+            let cfg_key = match MATRIX_KEYS.iter().find(|(matrix_key, _)| key == matrix_key) {
+                Some((_, cfg_key)) => syn::Ident::new(cfg_key, key.span()),
+                None => return Err(syn::Error::new(key.span(), format!("Unknown key '{key}', expected one of 'os', 'arch', 'family', 'vendor', 'env', 'pointer_width', 'endian', or 'feature'")))
+            };
+            if keys.iter().any(|(previous, _): &(syn::Ident, TargetName)| previous == &cfg_key) {
+                return Err(syn::Error::new(key.span(), format!("'{key}' is given twice")))
+            }
+            let _eq: Eq = entries.parse()?;
+            keys.push((cfg_key, TargetName::parse(&entries)?));
+            if !entries.is_empty() {
+                let _comma: Comma = entries.parse()?;
+            }
+        }
+
+        let mut keys = keys.into_iter();
+        let Some((cfg_key, name)) = keys.next() else {
+            return Err(syn::Error::new(brace.span.join(), "Expected at least one key, e.g. '{ os = linux, arch = arm }'"))
+        };
+        let qualifiers = keys.map(|(key, value)| (key, syn::LitStr::new(&value.to_string(), value.span()))).collect();
+
+        let path = match input.peek(Token![=>]) {
+            true => {
+                let _arrow: Token![=>] = input.parse()?;
+                Some(input.parse()?)
+            },
+            false => None
+        };
+
+        Ok(SpiTarget { values: Punctuated::from_iter([name.clone()]), name, cfg_key, path, qualifiers, predicate: None, matrix: true })
+    }
+}
+impl Parse for SpiTarget {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::token::Brace) {
+            return SpiTarget::parse_matrix(input)
+        }
+        let name = TargetName::parse(input)?;
+
+        let mut values = Punctuated::from_iter([name.clone()]);
+        let mut path = None;
+        let mut qualifiers = vec![];
+
+        if input.peek(syn::token::Paren) {
+            let group;
+            let _paren = parenthesized!(group in input);
+            while !group.is_empty() {
+                let key = syn::Ident::parse(&group)?;
+                let _eq: Eq = group.parse()?;
+                let value = match group.parse::<syn::LitStr>() {
+                    Ok(value) => value,
+                    Err(_) => {
+                        let value = syn::Ident::parse(&group)?;
+                        syn::LitStr::new(&value.unraw().to_string(), value.span())
+                    }
+                };
+                qualifiers.push((key, value));
+                if !group.is_empty() {
+                    let _comma: Comma = group.parse()?;
+                }
+            }
+        }
+
+        let mut predicate = None;
+        if input.peek(Eq) {
+            let _eq: Eq = input.parse()?;
+            if input.peek(syn::LitStr) {
+                path = Some(input.parse()?);
+            } else if input.peek(syn::Ident) {
+                let operator = syn::Ident::parse(input)?;
+                if operator != "not" && operator != "any" && operator != "all" {
+                    return Err(syn::Error::new(operator.span(), format!("Expected a cfg predicate starting with 'not', 'any', or 'all', but found '{operator}'")))
+                }
+                let group;
+                let _paren = parenthesized!(group in input);
+                let operands: TokenStream2 = group.parse()?;
+                predicate = Some(quote! { #operator(#operands) });
+                if input.peek(Token![=>]) {
+                    let _arrow: Token![=>] = input.parse()?;
+                    path = Some(input.parse()?);
+                }
+            } else {
+                let group;
+                let _bracket = bracketed!(group in input);
+                values = group.parse_terminated(TargetName::parse, Comma)?;
+            }
+        }
+
+        // the cfg key is that of the argument listing the target, set once it is parsed.
+        Ok(SpiTarget { name, cfg_key: syn::Ident::new("target_os", proc_macro2::Span::call_site()), values, path, qualifiers, predicate, matrix: false })
+    }
+}
+impl Parse for SpiAttributes {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut result = SpiAttributes {
+            module_path: syn::LitStr::new(".", input.span()),
+            targets: Default::default(),
+            unsupported: true,
+            unsupported_path: None,
+            unsupported_error: None,
+            file_pattern: None,
+            extension: syn::LitStr::new("rs", input.span()),
+            require_send_sync: Default::default(),
+            groups: vec![],
+            cfg_if_backend: false,
+            reexport_all: false,
+            factory: None,
+            default_constructors: false,
+            emit_target_list: false,
+            emit_current_target: false,
+            emit_platform_enum: false,
+            doc_cfg: false,
+            doc_target: None,
+            allow_shared_items: false,
+            strict_targets: false,
+            single: None,
+            require_all_targets: false,
+            source_dir: None
+        };
+        let mut target_args: Vec<syn::Ident> = vec![];
+
+        while !input.is_empty() {
+
+            let name = syn::Ident::parse(input)?;
+            let _eq: Eq = input.parse()?;
+
+            let target_key = TARGET_KEYS.iter().find(|(arg, _)| name == arg);
+
+            match (name.to_string().as_str(), target_key) {
+                (_, Some((_, cfg_key))) => {
+                    if target_args.iter().any(|previous| previous == &name) {
+                        return Err(syn::Error::new(name.span(), format!("'{name}' is given twice")))
+                    }
+                    let targets = if input.peek(syn::token::Bracket) {
+                        let targets;
+                        let _bracket = bracketed!(targets in input);
+                        targets.parse_terminated(SpiTarget::parse, Comma)?
+                    } else {
+                        // a single target may be written without brackets, e.g. `targets = linux`
+                        Punctuated::from_iter([SpiTarget::parse(input)?])
+                    };
+                    result.targets.extend(targets.into_iter().map(|mut target| {
+                        if !target.matrix {
+                            target.cfg_key = syn::Ident::new(cfg_key, name.span());
+                        }
+                        target
+                    }));
+                    target_args.push(name);
+                },
+                ("module_path", _) => {
+                    result.module_path = if input.peek(syn::LitStr) {
+                        input.parse()?
+                    } else {
+                        // a bare path such as `platform::impls` names the directory "platform/impls"
+                        let path = syn::Path::parse_mod_style(input)?;
+                        let segments: Vec<String> = path.segments.iter().map(|segment| segment.ident.unraw().to_string()).collect();
+                        syn::LitStr::new(&segments.join("/"), path.span())
+                    }
+                },
+                ("unsupported", _) => {
+                    if input.peek(syn::LitBool) {
+                        result.unsupported = input.parse::<syn::LitBool>()?.value
+                    } else {
+                        // `error("...")` fails the build on unsupported platforms with the message
+                        let form = syn::Ident::parse(input)?;
+                        if form != "error" {
+                            return Err(syn::Error::new(form.span(), format!("Expected 'true', 'false', or 'error(\"...\")', but found '{form}'")))
+                        }
+                        let message;
+                        let _paren = parenthesized!(message in input);
+                        result.unsupported = false;
+                        result.unsupported_error = Some(message.parse()?);
+                    }
+                },
+                ("unsupported_path" | "default", _) => {
+                    if result.unsupported_path.is_some() {
+                        return Err(syn::Error::new(name.span(), "Only one of 'unsupported_path' and 'default' may be given"))
+                    }
+                    result.unsupported_path = Some(input.parse()?)
+                },
+                ("file_pattern", _) => {
+                    let pattern: syn::LitStr = input.parse()?;
+                    if !pattern.value().contains("{target}") {
+                        return Err(syn::Error::new(pattern.span(), "'file_pattern' must contain '{target}', e.g. \"{target}_impl.rs\""))
+                    }
+                    result.file_pattern = Some(pattern)
+                },
+                ("extension", _) => {
+                    let extension: syn::LitStr = input.parse()?;
+                    // a suffix of one or more dot-separated parts, e.g. "rs" or "gen.rs"
+                    let value = extension.value();
+                    let plausible = value.split('.').all(
+                        |part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                    );
+                    if !plausible {
+                        return Err(syn::Error::new(extension.span(), format!("'{value}' is not a file extension, expected e.g. \"rs\" without a leading '.'")))
+                    }
+                    result.extension = extension
+                },
+                ("groups", _) => {
+                    let groups;
+                    let _brace = syn::braced!(groups in input);
+                    while !groups.is_empty() {
+                        let group_name = TargetName::parse(&groups)?;
+                        if result.groups.iter().any(|(existing, _)| existing.name == group_name.name) {
+                            return Err(syn::Error::new(group_name.span(), format!("Group '{group_name}' is defined twice")))
+                        }
+                        let _eq: Eq = groups.parse()?;
+                        let members;
+                        let _bracket = bracketed!(members in groups);
+                        result.groups.push((group_name, members.parse_terminated(TargetName::parse, Comma)?));
+                        if !groups.is_empty() {
+                            let _comma: Comma = groups.parse()?;
+                        }
+                    }
+                },
+                ("backend", _) => {
+                    let backend = syn::Ident::parse(input)?;
+                    result.cfg_if_backend = match backend.to_string().as_str() {
+                        "cfg" => false,
+                        "cfg_if" => true,
+                        _ => return Err(syn::Error::new(backend.span(), format!("Unknown backend '{backend}', expected 'cfg' or 'cfg_if'")))
+                    }
+                },
+                ("reexport_all", _) => {
+                    result.reexport_all = input.parse::<syn::LitBool>()?.value
+                },
+                ("factory", _) => {
+                    result.factory = Some(input.parse::<syn::LitStr>()?.parse()?)
+                },
+                ("default_constructors", _) => {
+                    result.default_constructors = input.parse::<syn::LitBool>()?.value
+                },
+                ("emit_target_list", _) => {
+                    result.emit_target_list = input.parse::<syn::LitBool>()?.value
+                },
+                ("emit_current_target", _) => {
+                    result.emit_current_target = input.parse::<syn::LitBool>()?.value
+                },
+                ("emit_platform_enum", _) => {
+                    result.emit_platform_enum = input.parse::<syn::LitBool>()?.value
+                },
+                ("allow_shared_items", _) => {
+                    result.allow_shared_items = input.parse::<syn::LitBool>()?.value
+                },
+                ("strict_targets", _) => {
+                    result.strict_targets = input.parse::<syn::LitBool>()?.value
+                },
+                ("single", _) => {
+                    result.single = Some(input.parse()?)
+                },
+                ("require_all_targets", _) => {
+                    result.require_all_targets = input.parse::<syn::LitBool>()?.value
+                },
+                ("doc_cfg", _) => {
+                    result.doc_cfg = input.parse::<syn::LitBool>()?.value
+                },
+                ("doc_target", _) => {
+                    result.doc_target = Some(input.parse()?)
+                },
+                ("require_send_sync", _) => {
+                    let types;
+                    let _bracket = bracketed!(types in input);
+                    result.require_send_sync = types.parse_terminated(syn::Type::parse, Comma)?;
+                },
+                _ => return Err(input.error(format!("Unexpected attribute '{name}'")))
+            }
+
+            let _comma = input.lookahead1();
+            if _comma.peek(Comma) {
+                let _comma: Comma = input.parse()?;
+            }
+        }
+
+        // groups may be defined after the targets naming them, so are only resolved once 
+        // every argument is parsed.
+        result.resolve_groups();
+        check_duplicate_targets(&result.targets)?;
+        if result.strict_targets {
+            check_known_target_os(&result.targets)?;
+        }
+        if let Some(doc_target) = &result.doc_target {
+            if result.doc_target_index().is_none() {
+                return Err(syn::Error::new(doc_target.span(), format!("'doc_target' names '{doc_target}', which is not a listed target")))
+            }
+        }
+
+        if let (Some(_), Some(path)) = (&result.unsupported_error, &result.unsupported_path) {
+            return Err(syn::Error::new(path.span(), "'unsupported = error(...)' imports no fallback file, so none may be named"))
+        }
+
+        // without targets every platform would import the fallback module, which is 
+        // only intended when it names a default implementation.
+        if let Some(single) = &result.single {
+            if !result.targets.is_empty() || result.unsupported_path.is_some() || result.unsupported_error.is_some() {
+                return Err(syn::Error::new(single.span(), "'single' imports the same file on every platform, so no targets or fallback may be given"))
+            }
+        } else if result.targets.is_empty() && result.unsupported_path.is_none() {
+            let span = target_args.first().map_or_else(proc_macro2::Span::call_site, |arg| arg.span());
+            return Err(syn::Error::new(span, "At least one target is required, e.g. 'targets = [linux]'"))
+        }
+
+        Ok(result)
+    }
+}
+
+/// An SPI module declaration, split into the module file import and its contract.
+pub struct SpiModule {
+    /// the inline module declaration, rewritten as a module file import without a path
+    pub mod_import_decl: syn::ItemMod,
+    /// items hoisted into the parent module, referring to items of the platform module
+    pub aliases: Vec<syn::Item>,
+    /// static assertions checking each platform module against the contract
+    pub assertions: Vec<SpiAssertion>
+}
+
+/// A static assertion generated from a contract item.
+pub enum SpiAssertion {
+    /// the type satisfies every bound, from `impl Trait for Type {}` or 
+    /// `assert_impl_all!(Type: TraitA + TraitB + 'static);`
+    ImplAll(syn::Type, Vec<syn::TypeParamBound>),
+    /// the type implements at least one trait, from `assert_impl_any!(Type: TraitA, TraitB);`
+    ImplAny(syn::Type, Vec<syn::Path>),
+    /// the type does not implement all of the traits together, from `impl !Trait for Type {}`
+    NotImplAll(syn::Type, Vec<syn::Path>),
+    /// the type implements none of the traits, from `assert_not_impl_any!(Type: TraitA, TraitB);`
+    NotImplAny(syn::Type, Vec<syn::Path>),
+    /// the types are all the same type, from `assert_type_eq!(TypeA == TypeB);`
+    TypeEq(Vec<syn::Type>),
+    /// the type's size in bytes, from `assert_size!(Type == 16);`
+    Size(syn::Type, syn::LitInt),
+    /// the traits can be made into trait objects, from `assert_obj_safe!(Trait);`
+    ObjSafe(Vec<syn::Path>),
+    /// the type implements every trait for all generic parameters satisfying their bounds,
+    /// from `impl<T: Bound> Trait for Type<T> {}`
+    GenericImplAll(syn::Generics, syn::Type, Vec<syn::Path>),
+    /// an assertion only made when the cfg predicates hold, from a `#[cfg]` on the 
+    /// contract item or on the alias it names
+    Gated(Vec<syn::Attribute>, Box<SpiAssertion>)
+}
+impl SpiAssertion {
+    // a name describing the assertion, e.g. "_ASSERT_PlatformService_impl_SomeTrait" for 
+    // `impl SomeTrait for PlatformService {}`, so that diagnostics identify the contract item.
+    fn name(&self) -> String {
+        fn joined<T: ToTokens>(items: &[T], separator: &str) -> String {
+            items.iter().map(|item| item.to_token_stream().to_string()).collect::<Vec<_>>().join(separator)
+        }
+        let description = match self {
+            SpiAssertion::ImplAll(ty, bounds) => format!("{} impl {}", ty.to_token_stream(), joined(bounds, " ")),
+            SpiAssertion::ImplAny(ty, traits) => format!("{} impl any {}", ty.to_token_stream(), joined(traits, " ")),
+            SpiAssertion::NotImplAll(ty, traits) => format!("{} not impl {}", ty.to_token_stream(), joined(traits, " ")),
+            SpiAssertion::NotImplAny(ty, traits) => format!("{} not impl any {}", ty.to_token_stream(), joined(traits, " ")),
+            SpiAssertion::TypeEq(types) => joined(types, " eq "),
+            SpiAssertion::Size(ty, size) => format!("{} size {size}", ty.to_token_stream()),
+            SpiAssertion::ObjSafe(traits) => format!("{} obj safe", joined(traits, " ")),
+            SpiAssertion::GenericImplAll(_, ty, traits) => format!("{} impl {}", ty.to_token_stream(), joined(traits, " ")),
+            SpiAssertion::Gated(_, assertion) => return assertion.name()
+        };
+
+        // any run of characters not valid in an identifier becomes a single underscore.
+        let mut name = String::from("_ASSERT");
+        for word in description.split(|c: char| !c.is_ascii_alphanumeric() && c != '_').filter(|word| !word.is_empty()) {
+            name.push('_');
+            name.push_str(word);
+        }
+        name
+    }
+
+    // names of the aliases the assertion is about, e.g. "PlatformService" for 
+    // `impl Send for PlatformService {}`
+    fn subject_names(&self) -> Vec<syn::Ident> {
+        fn path_root(path: &syn::Path) -> Option<syn::Ident> {
+            path.segments.first().map(|segment| segment.ident.clone())
+        }
+        fn type_root(ty: &syn::Type) -> Option<syn::Ident> {
+            match ty {
+                syn::Type::Path(type_path) => path_root(&type_path.path),
+                _ => None
+            }
+        }
+
+        match self {
+            SpiAssertion::ImplAll(ty, _) | SpiAssertion::ImplAny(ty, _) | SpiAssertion::NotImplAll(ty, _) | SpiAssertion::NotImplAny(ty, _)
+                | SpiAssertion::Size(ty, _) | SpiAssertion::GenericImplAll(_, ty, _) => type_root(ty).into_iter().collect(),
+            SpiAssertion::TypeEq(types) => types.iter().filter_map(type_root).collect(),
+            SpiAssertion::ObjSafe(traits) => traits.iter().filter_map(path_root).collect(),
+            SpiAssertion::Gated(_, assertion) => assertion.subject_names()
+        }
+    }
+}
+fn traits_span<T: Spanned>(traits: &[T]) -> proc_macro2::Span {
+    traits.first().map_or_else(proc_macro2::Span::call_site, |path| path.span())
+}
+
+// assertions are generated directly rather than through static_assertions, so that users 
+// need no dependency beyond platform_spi. Each is checked by the type checker alone, inside
+// an anonymous const that is never evaluated at runtime. Library items are named through 
+// `::core`, so that assertions build in no_std crates and regardless of local names.
+impl ToTokens for SpiAssertion {
+    fn to_tokens(&self, tokens: &mut TokenStream2) {
+        // kept at the call site within spanned helpers, as lints would otherwise report the 
+        // bound as redundant for a Sized trait such as Default.
+        let maybe_sized = quote! { ?Sized };
+        tokens.extend(match self {
+            // helpers are spanned at the contract's traits, so that notes about their bounds 
+            // point at the contract item rather than at the attribute.
+            SpiAssertion::ImplAll(ty, traits) => quote_spanned! { traits_span(traits) =>
+                const _: fn() = || {
+                    fn assert_impl_all<T: #maybe_sized #(+ #traits)*>() {}
+                    assert_impl_all::<#ty>();
+                };
+            },
+            // each wrapper only has an `impl_any_token` method if the type implements its 
+            // trait, otherwise the method call auto-derefs to the previous wrapper, and finally 
+            // to the fallback, which has no such method.
+            SpiAssertion::ImplAny(ty, traits) => quote! {
+                const _: fn() = || {
+                    struct Fallback;
+                    struct Token;
+                    let previous = Fallback;
+                    #(
+                        let previous = {
+                            struct Wrapper<T: ?Sized, N>(::core::marker::PhantomData<T>, N);
+                            impl<T: ?Sized, N> ::core::ops::Deref for Wrapper<T, N> {
+                                type Target = N;
+                                fn deref(&self) -> &N { &self.1 }
+                            }
+                            impl<T: ?Sized + #traits, N> Wrapper<T, N> {
+                                #[allow(dead_code)]
+                                fn impl_any_token(&self) -> Token { Token }
+                            }
+                            Wrapper::<#ty, _>(::core::marker::PhantomData, previous)
+                        };
+                    )*
+                    let _: Token = previous.impl_any_token();
+                };
+            },
+            // `some_item` is ambiguous, and so fails to compile, only if the type implements
+            // the traits and so matches both impls.
+            SpiAssertion::NotImplAll(ty, traits) => quote_spanned! { traits_span(traits) =>
+                const _: fn() = || {
+                    trait AmbiguousIfImpl<A> {
+                        fn some_item() {}
+                    }
+                    impl<T: ?Sized> AmbiguousIfImpl<()> for T {}
+                    #[allow(dead_code)]
+                    struct Invalid;
+                    impl<T: #maybe_sized #(+ #traits)*> AmbiguousIfImpl<Invalid> for T {}
+                    let _ = <#ty as AmbiguousIfImpl<_>>::some_item;
+                };
+            },
+            SpiAssertion::NotImplAny(ty, traits) => traits.iter()
+                .map(|path| SpiAssertion::NotImplAll(ty.clone(), vec![path.clone()]).into_token_stream())
+                .collect(),
+            SpiAssertion::TypeEq(types) => {
+                let (first, rest) = types.split_first().expect("assert_type_eq! requires at least two types");
+                quote! {
+                    const _: fn() = || {
+                        trait TypeEq {
+                            type This: ?Sized;
+                        }
+                        impl<T: ?Sized> TypeEq for T {
+                            type This = Self;
+                        }
+                        fn assert_type_eq<T: ?Sized + TypeEq<This = U>, U: ?Sized>() {}
+                        #(assert_type_eq::<#first, #rest>();)*
+                    };
+                }
+            },
+            SpiAssertion::Size(ty, size) => quote! {
+                const _: () = ::core::assert!(::core::mem::size_of::<#ty>() == #size, "platform type has the wrong size");
+            },
+            SpiAssertion::ObjSafe(traits) => quote! {
+                #(const _: ::core::option::Option<&dyn #traits> = ::core::option::Option::None;)*
+            },
+            // `bound` can only be called from `check` if the asserted impl holds 
+            // for every choice of generic parameters allowed by the contract's bounds.
+            SpiAssertion::GenericImplAll(generics, ty, traits) => {
+                let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+                let mut bound_generics = generics.clone();
+                bound_generics.make_where_clause().predicates.push(syn::parse_quote!(#ty : #(#traits)+*));
+                let bound_where_clause = &bound_generics.where_clause;
+                let turbofish = ty_generics.as_turbofish();
+                quote_spanned! { traits_span(traits) =>
+                    const _: () = {
+                        #[allow(dead_code)]
+                        fn bound #impl_generics () #bound_where_clause {}
+                        #[allow(dead_code)]
+                        fn check #impl_generics () #where_clause { bound #turbofish (); }
+                    };
+                }
+            },
+            SpiAssertion::Gated(cfgs, assertion) => quote! {
+                #(#cfgs)*
+                const _: () = { #assertion };
+            }
+        })
+    }
+}
+
+// each assertion scoped within a const named after it, with a numeric suffix where 
+// several assertions would share a name.
+fn named_assertions<'a>(assertions: impl IntoIterator<Item = &'a SpiAssertion>) -> Vec<TokenStream2> {
+    let mut names: Vec<String> = vec![];
+    assertions.into_iter().map(|assertion| {
+        let base = assertion.name();
+        let count = names.iter().filter(|name| **name == base).count();
+        names.push(base.clone());
+        let name = match count {
+            0 => syn::Ident::new(&base, proc_macro2::Span::call_site()),
+            _ => syn::Ident::new(&format!("{base}_{}", count + 1), proc_macro2::Span::call_site())
+        };
+        quote! {
+            #[allow(non_upper_case_globals, dead_code)]
+            const #name: () = { #assertion };
+        }
+    }).collect()
+}
+
+/// Arguments of a bounds assertion directive, e.g. `assert_impl_all!(Type: TraitA + TraitB + 'static);`
+struct BoundsDirective {
+    ty: syn::Type,
+    _colon: Token![:],
+    bounds: Punctuated<syn::TypeParamBound, Token![+]>
+}
+impl Parse for BoundsDirective {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(BoundsDirective {
+            ty: input.parse()?,
+            _colon: input.parse()?,
+            bounds: Punctuated::parse_separated_nonempty(input)?
+        })
+    }
+}
+
+/// Arguments of a contract assertion directive, e.g. `assert_impl_any!(Type: TraitA, TraitB);`
+struct TraitsDirective {
+    ty: syn::Type,
+    _colon: Token![:],
+    traits: Punctuated<syn::Path, Comma>
+}
+impl Parse for TraitsDirective {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(TraitsDirective {
+            ty: input.parse()?,
+            _colon: input.parse()?,
+            traits: Punctuated::parse_terminated(input)?
+        })
+    }
+}
+// implementing TryFrom rather than Parse allows us to reuse most of the parse logic
+// from ItemMod, plus be a little more fine-grained with errors (e.g. we can report 
+// multiple errors, limit our errors to specific spans).
+impl TryFrom<&syn::ItemMod> for SpiModule {
+    type Error = TokenStream2;
+
+    fn try_from(mod_decl: &syn::ItemMod) -> Result<Self, Self::Error> {
+        let parent_module = syn::Path::from(mod_decl.ident.clone());
+
+        let mod_aliases = check_spi_items(mod_decl)?;
+        let (aliases, assertions) = hoist_aliases_and_generate_impls(mod_aliases, parent_module)?;
+
+        // cfgs on the module itself also gate everything naming it, so that the module is 
+        // only compiled where they hold as well as the cfg of a target.
+        let module_cfgs = cfg_attrs(&mod_decl.attrs);
+        let aliases = aliases.into_iter().map(|mut alias| {
+            if let Some(attrs) = item_attrs_mut(&mut alias) {
+                attrs.splice(0..0, module_cfgs.iter().cloned());
+            }
+            alias
+        }).collect();
+        let assertions = assertions.into_iter()
+            .map(|assertion| gate_assertion(assertion, &module_cfgs, &[]))
+            .collect();
+
+        let mod_import_decl = syn::ItemMod {
+            attrs: mod_decl.attrs.clone(),
+            vis: mod_decl.vis.clone(),
+            unsafety: mod_decl.unsafety,
+            mod_token: mod_decl.mod_token,
+            ident: mod_decl.ident.clone(),
+            content: None,
+            semi: Some(Semi(mod_decl.ident.span())),
+        };
+
+        Ok(Self { mod_import_decl, aliases, assertions })
+    }
+}
+
+// items with a body or definition of their own, which are code shared by every platform 
+// rather than contract markers, e.g. a fn with a body or a struct. An inherent impl is 
+// never a contract, so it is shared even when empty.
+fn is_shared_item(item: &syn::Item) -> bool {
+    match item {
+        syn::Item::Fn(_) | syn::Item::Const(_) | syn::Item::Static(_) | syn::Item::Struct(_)
+            | syn::Item::Enum(_) | syn::Item::Union(_) => true,
+        syn::Item::Impl(impl_item) => impl_item.trait_.is_none() || !impl_item.items.is_empty(),
+        syn::Item::Trait(trait_item) => !trait_item.items.is_empty(),
+        syn::Item::Macro(item_macro) => item_macro.mac.path.is_ident("macro_rules"),
+        _ => false
+    }
+}
+
+fn check_spi_items(mod_decl: &syn::ItemMod) -> Result<&[syn::Item], TokenStream2> {
+    match &mod_decl.content {
+        Some((_, content)) => 
+            Ok(content),
+        None => 
+            Err(syn::Error::new(mod_decl.ident.span(), "External module imports are not supported, only inline module declarations.").to_compile_error())
+    }
+}
+
+/// Hoists the contract items of an SPI module into the parent module, as paths within 
+/// `parent_module`, along with the assertions made by its "impl" declarations and directives.
+pub fn hoist_aliases_and_generate_impls(mod_aliases: &[syn::Item], parent_module: syn::Path) -> Result<(Vec<syn::Item>, Vec<SpiAssertion>), TokenStream2> {
+    let mut invalid_items: Vec<TokenStream2> = vec![];
+    let mut aliases: Vec<syn::Item> = vec![];
+    let mut assertions: Vec<SpiAssertion> = vec![];
+
+    for item in mod_aliases {
+        let assertion = match item {
+            syn::Item::Impl(impl_item) => Some((&impl_item.attrs, impl_contract_assertion(impl_item))),
+            syn::Item::Macro(directive) => Some((
+                &directive.attrs,
+                parse_assertion_directive(directive, &alias_names(&aliases)).map_err(|error| error.to_compile_error())
+            )),
+            _ => None
+        };
+        if let Some((attrs, assertion)) = assertion {
+            match assertion {
+                Ok(assertion) => assertions.push(gate_assertion(assertion, attrs, &aliases)),
+                Err(diagnostic) => invalid_items.push(diagnostic)
+            }
+            continue;
+        }
+        let hoisted = match item {
+            syn::Item::Type(alias) => hoist_type_alias(alias, &parent_module).map(|item| vec![item]),
+            syn::Item::Use(alias) => hoist_use_alias(alias, &parent_module).map(|item| vec![item]),
+            syn::Item::Const(constant) => Err(syn::Error::new(constant.expr.span(), "Const contracts must be declared without a value, e.g. 'pub const NAME: Type;'").to_compile_error()),
+            syn::Item::Static(static_item) => Err(syn::Error::new(static_item.expr.span(), "Static contracts must be declared without a value, e.g. 'pub static NAME: Type;'").to_compile_error()),
+            syn::Item::Fn(function) => Err(syn::Error::new(
+                function.block.span(), "Function contracts must be declared without a body, e.g. 'pub fn name(arg: Type) -> Type;'"
+            ).to_compile_error()),
+            syn::Item::Verbatim(tokens) => hoist_verbatim_contract(item, tokens.clone(), &parent_module),
+            syn::Item::Trait(trait_item) => hoist_trait_reexport(trait_item, &parent_module).map(|item| vec![item]),
+            syn::Item::Mod(submodule) => hoist_submodule(submodule, &parent_module).map(|item| vec![item]),
+            _ => Err(unsupported_item_error(item))
+        };
+        match hoisted.and_then(|items| items.into_iter().map(substitute_spi_doc).collect::<Result<Vec<_>, _>>()) {
+            Ok(items) => aliases.extend(items),
+            Err(diagnostic) => invalid_items.push(diagnostic),
+        }
+    }
+
+    if !invalid_items.is_empty() {
+        return Err(invalid_items.into_iter().collect())
+    }
+
+    let mut assertions = group_impl_assertions(assertions);
+
+    // identical contract lines, such as those repeated by a user's own macros, are 
+    // asserted once, keeping the first for its spans.
+    let mut seen = HashSet::new();
+    assertions.retain(|assertion| seen.insert(assertion.to_token_stream().to_string()));
+
+    Ok((aliases, assertions))
+}
+
+// the bounds a type must satisfy are combined into a single assertion, placed where the 
+// type's first assertion was, e.g. `impl A for T {}` and `impl B for T {}` assert `T: A + B`.
+fn group_impl_assertions(assertions: Vec<SpiAssertion>) -> Vec<SpiAssertion> {
+    // the type and any gating cfgs, which must both match for assertions to be combined.
+    fn group_key(assertion: &SpiAssertion) -> Option<String> {
+        match assertion {
+            SpiAssertion::ImplAll(ty, _) => Some(ty.to_token_stream().to_string()),
+            SpiAssertion::Gated(cfgs, inner) => group_key(inner).map(|key| format!("{} {key}", quote! { #(#cfgs)* })),
+            _ => None
+        }
+    }
+    fn bounds_mut(assertion: &mut SpiAssertion) -> Option<&mut Vec<syn::TypeParamBound>> {
+        match assertion {
+            SpiAssertion::ImplAll(_, bounds) => Some(bounds),
+            SpiAssertion::Gated(_, inner) => bounds_mut(inner),
+            _ => None
+        }
+    }
+
+    let mut grouped: Vec<SpiAssertion> = vec![];
+    for mut assertion in assertions {
+        let key = group_key(&assertion);
+        let group = key.and_then(|key| grouped.iter_mut().find(|existing| group_key(existing).as_ref() == Some(&key)));
+        match (group.and_then(bounds_mut), bounds_mut(&mut assertion)) {
+            (Some(existing), Some(bounds)) => {
+                for bound in bounds.drain(..) {
+                    let tokens = bound.to_token_stream().to_string();
+                    if !existing.iter().any(|existing| existing.to_token_stream().to_string() == tokens) {
+                        existing.push(bound);
+                    }
+                }
+            },
+            _ => grouped.push(assertion)
+        }
+    }
+    grouped
+}
+
+fn impl_contract_assertion(impl_item: &syn::ItemImpl) -> Result<SpiAssertion, TokenStream2> {
+    if let (0, Some((negation, path, _))) = (impl_item.items.len(), &impl_item.trait_) {
+        let self_ty = *impl_item.self_ty.clone();
+        let generics = &impl_item.generics;
+        match (negation, generics.params.is_empty() && generics.where_clause.is_none()) {
+            (None, true) => Ok(SpiAssertion::ImplAll(self_ty, vec![syn::parse_quote!(#path)])),
+            (None, false) => Ok(SpiAssertion::GenericImplAll(generics.clone(), self_ty, vec![path.clone()])),
+            (Some(_), true) => Ok(SpiAssertion::NotImplAll(self_ty, vec![path.clone()])),
+            (Some(_), false) => {
+                let span = match &generics.where_clause {
+                    Some(where_clause) if generics.params.is_empty() => where_clause.span(),
+                    _ => generics.span()
+                };
+                Err(syn::Error::new(span, "Negative impl contracts cannot be generic").to_compile_error())
+            }
+        }
+    } else {
+        // point at the body when it isn't empty, otherwise at the type missing a trait.
+        let span = match &impl_item.trait_ {
+            Some(_) => impl_item.brace_token.span.join(),
+            None => impl_item.self_ty.span()
+        };
+        Err(syn::Error::new(
+            span, 
+            "Impl block is incorrectly formed, only format of 'impl Trait for Type {}' is allowed"
+        ).to_compile_error())
+    }
+}
+
+// an assertion about an alias gated by #[cfg] is gated the same way, along with any #[cfg]
+// on the assertion's own contract item, so that it disappears together with the alias.
+fn gate_assertion(assertion: SpiAssertion, attrs: &[syn::Attribute], aliases: &[syn::Item]) -> SpiAssertion {
+    let mut cfgs: Vec<syn::Attribute> = vec![];
+    let subject_cfgs = assertion.subject_names().into_iter().flat_map(|name| {
+        aliases.iter()
+            .filter(|alias| declared_names(alias).contains(&name))
+            .flat_map(|alias| cfg_attrs(item_attrs(alias)))
+            .collect::<Vec<_>>()
+    });
+    for cfg in cfg_attrs(attrs).into_iter().chain(subject_cfgs) {
+        let tokens = cfg.to_token_stream().to_string();
+        if !cfgs.iter().any(|existing| existing.to_token_stream().to_string() == tokens) {
+            cfgs.push(cfg);
+        }
+    }
+
+    match cfgs.is_empty() {
+        true => assertion,
+        false => SpiAssertion::Gated(cfgs, Box::new(assertion))
+    }
+}
+
+fn cfg_attrs(attrs: &[syn::Attribute]) -> Vec<syn::Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("cfg")).cloned().collect()
+}
+
+fn item_attrs(item: &syn::Item) -> &[syn::Attribute] {
+    match item {
+        syn::Item::Type(alias) => &alias.attrs,
+        syn::Item::Const(constant) => &constant.attrs,
+        syn::Item::Use(alias) => &alias.attrs,
+        syn::Item::Mod(submodule) => &submodule.attrs,
+        _ => &[]
+    }
+}
+fn item_attrs_mut(item: &mut syn::Item) -> Option<&mut Vec<syn::Attribute>> {
+    match item {
+        syn::Item::Type(alias) => Some(&mut alias.attrs),
+        syn::Item::Const(constant) => Some(&mut constant.attrs),
+        syn::Item::Use(alias) => Some(&mut alias.attrs),
+        syn::Item::Mod(submodule) => Some(&mut submodule.attrs),
+        _ => None
+    }
+}
+
+// `#[spi_doc = "..."]` replaces the docs of the hoisted item, so that a contract item's own 
+// docs can be notes for the platform implementers that stay out of the public API.
+fn substitute_spi_doc(mut item: syn::Item) -> Result<syn::Item, TokenStream2> {
+    let Some(attrs) = item_attrs_mut(&mut item) else {
+        return Ok(item)
+    };
+    if !attrs.iter().any(|attr| attr.path().is_ident("spi_doc")) {
+        return Ok(item)
+    }
+
+    let mut docs = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("spi_doc")) {
+        let doc = match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue { value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(doc), .. }), .. }) => doc,
+            _ => return Err(syn::Error::new(attr.span(), "Expected a string, e.g. '#[spi_doc = \"...\"]'").to_compile_error())
+        };
+        docs.push(syn::parse_quote!(#[doc = #doc]));
+    }
+    attrs.retain(|attr| !attr.path().is_ident("spi_doc") && !attr.path().is_ident("doc"));
+    attrs.splice(0..0, docs);
+
+    Ok(item)
+}
+
+// `#[doc(cfg(...))]` repeating the item's own cfgs, for the nightly doc_cfg feature. The 
+// items of a hoisted submodule are annotated with their own cfgs too.
+fn annotate_doc_cfg(mut item: syn::Item) -> syn::Item {
+    if let syn::Item::Mod(syn::ItemMod { content: Some((_, items)), .. }) = &mut item {
+        *items = std::mem::take(items).into_iter().map(annotate_doc_cfg).collect();
+    }
+    let Some(attrs) = item_attrs_mut(&mut item) else {
+        return item
+    };
+    let predicates: Vec<TokenStream2> = cfg_attrs(attrs).iter()
+        .filter_map(|attr| attr.parse_args::<TokenStream2>().ok())
+        .collect();
+    let doc_cfg: syn::Attribute = match predicates.as_slice() {
+        [] => return item,
+        [predicate] => syn::parse_quote!(#[doc(cfg(#predicate))]),
+        _ => syn::parse_quote!(#[doc(cfg(all(#(#predicates),*)))])
+    };
+    attrs.push(doc_cfg);
+
+    item
+}
+
+fn parse_assertion_directive(directive: &syn::ItemMacro, declared: &[syn::Ident]) -> syn::Result<SpiAssertion> {
+    let name = directive.mac.path.require_ident()?;
+    match name.to_string().as_str() {
+        "assert_impl_all" => {
+            let args: BoundsDirective = directive.mac.parse_body()?;
+            Ok(SpiAssertion::ImplAll(args.ty, args.bounds.into_iter().collect()))
+        },
+        "assert_impl_any" => {
+            let args: TraitsDirective = directive.mac.parse_body()?;
+            Ok(SpiAssertion::ImplAny(args.ty, args.traits.into_iter().collect()))
+        },
+        "assert_not_impl_any" => {
+            let args: TraitsDirective = directive.mac.parse_body()?;
+            Ok(SpiAssertion::NotImplAny(args.ty, args.traits.into_iter().collect()))
+        },
+        "assert_type_eq" => {
+            let types = directive.mac.parse_body_with(Punctuated::<syn::Type, Token![==]>::parse_separated_nonempty)?;
+            if types.len() < 2 {
+                return Err(syn::Error::new(directive.mac.span(), "assert_type_eq! requires at least two types, e.g. 'assert_type_eq!(TypeA == TypeB);'"))
+            }
+            for ty in &types {
+                check_declared_alias(ty, declared)?;
+            }
+            Ok(SpiAssertion::TypeEq(types.into_iter().collect()))
+        },
+        "assert_size" => {
+            let args: SizeDirective = directive.mac.parse_body()?;
+            check_declared_alias(&args.ty, declared)?;
+            Ok(SpiAssertion::Size(args.ty, args.size))
+        },
+        "assert_obj_safe" => {
+            let traits = directive.mac.parse_body_with(Punctuated::<syn::Path, Comma>::parse_terminated)?;
+            for path in &traits {
+                check_declared_alias(&syn::Type::Path(syn::TypePath { qself: None, path: path.clone() }), declared)?;
+            }
+            Ok(SpiAssertion::ObjSafe(traits.into_iter().collect()))
+        },
+        _ => Err(syn::Error::new(name.span(), format!("Unknown contract directive '{name}!'")))
+    }
+}
+
+fn unsupported_item_error(item: &syn::Item) -> TokenStream2 {
+    let found = match item {
+        syn::Item::Struct(_) => "a `struct`",
+        syn::Item::Enum(_) => "an `enum`",
+        syn::Item::Union(_) => "a `union`",
+        syn::Item::TraitAlias(_) => "a trait alias",
+        syn::Item::ExternCrate(_) => "an `extern crate`",
+        syn::Item::ForeignMod(_) => "an `extern` block",
+        _ => "an unrecognized item"
+    };
+    syn::Error::new(
+        item.span(), 
+        format!("Only 'type', 'use', 'const', 'static', 'fn', 'trait', 'mod', and 'impl' items, and the 'assert_impl_all!', 'assert_impl_any!', \
+            'assert_not_impl_any!', 'assert_type_eq!', 'assert_size!', and 'assert_obj_safe!' directives are supported in an SPI module declaration but found {found}")
+    ).to_compile_error()
+}
+
+// items without a value, such as const and static contracts, are not valid rust items and
+// so are left unparsed by syn.
+fn hoist_verbatim_contract(item: &syn::Item, tokens: TokenStream2, parent_module: &syn::Path) -> Result<Vec<syn::Item>, TokenStream2> {
+    if let Ok(constant) = syn::parse2::<ConstContract>(tokens.clone()) {
+        hoist_const_contract(&constant, parent_module).map(|item| vec![item])
+    } else if let Ok(static_item) = syn::parse2::<syn::ForeignItemStatic>(tokens.clone()) {
+        hoist_static_contract(&static_item, parent_module)
+    } else if let Ok(function) = syn::parse2::<syn::ForeignItemFn>(tokens) {
+        hoist_fn_contract(&function, parent_module)
+    } else {
+        Err(unsupported_item_error(item))
+    }
+}
+
+fn hoist_type_alias(alias: &syn::ItemType, parent_module: &syn::Path) -> Result<syn::Item, TokenStream2> {
+    let mut hoisted = alias.clone();
+    let params: Vec<syn::Ident> = alias.generics.type_params().map(|param| param.ident.clone()).collect();
+    hoisted.ty = Box::new(hoist_aliased_type(&alias.ty, parent_module, &params)?);
+
+    Ok(syn::Item::Type(hoisted))
+}
+
+// prefixes each path within the aliased type that names an item of the platform module,
+// leaving rooted paths, primitives, standard library types, and the alias's own type 
+// parameters as written.
+fn hoist_aliased_type(ty: &syn::Type, parent_module: &syn::Path, params: &[syn::Ident]) -> Result<syn::Type, TokenStream2> {
+    match ty {
+        // a projection such as `<ReaderImpl as Iterator>::Item` names the platform type in 
+        // its qself, leaving the trait and associated item as written.
+        syn::Type::Path(type_path) if type_path.qself.is_some() => {
+            let mut hoisted_path = type_path.clone();
+            if let Some(qself) = hoisted_path.qself.as_mut() {
+                *qself.ty = hoist_aliased_type(&qself.ty, parent_module, params)?;
+            }
+
+            Ok(syn::Type::Path(hoisted_path))
+        },
+        syn::Type::Path(type_path) if is_rooted_path(&type_path.path) || is_primitive_path(&type_path.path)
+            || type_path.path.get_ident().is_some_and(|ident| params.contains(ident)) => Ok(ty.clone()),
+        // a standard library type such as `Option<StreamImpl>` is left as written, but its 
+        // type arguments may still name platform types.
+        syn::Type::Path(type_path) if is_std_path(&type_path.path) || is_prelude_type_path(&type_path.path) => {
+            let mut hoisted_path = type_path.clone();
+            for segment in hoisted_path.path.segments.iter_mut() {
+                if let syn::PathArguments::AngleBracketed(arguments) = &mut segment.arguments {
+                    for argument in arguments.args.iter_mut() {
+                        match argument {
+                            syn::GenericArgument::Type(ty) => *ty = hoist_aliased_type(ty, parent_module, params)?,
+                            syn::GenericArgument::AssocType(assoc) => assoc.ty = hoist_aliased_type(&assoc.ty, parent_module, params)?,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+
+            Ok(syn::Type::Path(hoisted_path))
+        },
+        syn::Type::Path(type_path) => {
+            let mut hoisted_path = type_path.clone();
+            prefix_parent_module(&mut hoisted_path.path, parent_module);
+
+            Ok(syn::Type::Path(hoisted_path))
+        },
+        syn::Type::Reference(reference) => {
+            let mut hoisted = reference.clone();
+            hoisted.elem = Box::new(hoist_aliased_type(&reference.elem, parent_module, params)?);
+
+            Ok(syn::Type::Reference(hoisted))
+        },
+        syn::Type::Tuple(tuple) => {
+            let mut hoisted = tuple.clone();
+            for elem in hoisted.elems.iter_mut() {
+                *elem = hoist_aliased_type(elem, parent_module, params)?;
+            }
+
+            Ok(syn::Type::Tuple(hoisted))
+        },
+        syn::Type::Array(array) => {
+            let mut hoisted = array.clone();
+            hoisted.elem = Box::new(hoist_aliased_type(&array.elem, parent_module, params)?);
+
+            Ok(syn::Type::Array(hoisted))
+        },
+        syn::Type::BareFn(function) => {
+            let mut hoisted = function.clone();
+            for input in hoisted.inputs.iter_mut() {
+                input.ty = hoist_aliased_type(&input.ty, parent_module, params)?;
+            }
+            if let syn::ReturnType::Type(_, output) = &mut hoisted.output {
+                **output = hoist_aliased_type(output, parent_module, params)?;
+            }
+
+            Ok(syn::Type::BareFn(hoisted))
+        },
+        // only traits of the platform module are prefixed, so `dyn Backend + Send` names 
+        // the platform's Backend but the standard Send, and `dyn std::fmt::Debug` is left alone.
+        syn::Type::TraitObject(object) => {
+            let mut hoisted = object.clone();
+            for bound in hoisted.bounds.iter_mut() {
+                if let syn::TypeParamBound::Trait(bound) = bound {
+                    if !is_rooted_path(&bound.path) && !is_std_path(&bound.path) && !is_prelude_trait_path(&bound.path) {
+                        prefix_parent_module(&mut bound.path, parent_module);
+                    }
+                }
+            }
+
+            Ok(syn::Type::TraitObject(hoisted))
+        },
+        _ => {
+            Err(syn::Error::new(ty.span(), "Only path, reference, tuple, array, function pointer, and trait object aliases are supported in an SPI module declaration").to_compile_error())
+        }
+    }
+}
+
+// a path already written through the parent module, e.g. `platform::StreamImpl`, is left
+// as written rather than prefixed a second time.
+fn prefix_parent_module(path: &mut syn::Path, parent_module: &syn::Path) {
+    let already_prefixed = path.segments.len() > parent_module.segments.len()
+        && path.segments.iter().zip(parent_module.segments.iter()).all(|(segment, parent_segment)| segment.ident == parent_segment.ident);
+    if !already_prefixed {
+        for (index, parent_segment) in parent_module.segments.iter().enumerate() {
+            path.segments.insert(index, parent_segment.clone());
+        }
+    }
+}
+
+const PRIMITIVE_TYPES: &[&str] = &[
+    "bool", "char", "str", "f32", "f64",
+    "i8", "i16", "i32", "i64", "i128", "isize",
+    "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+fn is_primitive_path(path: &syn::Path) -> bool {
+    path.get_ident().is_some_and(|ident| PRIMITIVE_TYPES.iter().any(|primitive| ident == primitive))
+}
+
+/// types of the standard prelude, which an alias can name without an import.
+const PRELUDE_TYPES: &[&str] = &["Option", "Result", "Vec", "String", "Box"];
+
+fn is_prelude_type_path(path: &syn::Path) -> bool {
+    matches!(path.segments.first(), Some(segment) if path.segments.len() == 1 && PRELUDE_TYPES.iter().any(|name| segment.ident == name))
+}
+
+// paths into the standard library, e.g. `std::time::Duration`, which no platform module shadows.
+fn is_std_path(path: &syn::Path) -> bool {
+    matches!(path.segments.first(), Some(segment) if path.segments.len() > 1 && ["std", "core", "alloc"].iter().any(|name| segment.ident == name))
+}
+
+/// traits of the standard prelude, which a trait object can name without an import.
+const PRELUDE_TRAITS: &[&str] = &[
+    "Send", "Sync", "Sized", "Unpin", "Copy", "Clone", "Drop",
+    "Fn", "FnMut", "FnOnce", "AsRef", "AsMut", "From", "Into", "TryFrom", "TryInto",
+    "Iterator", "IntoIterator", "DoubleEndedIterator", "ExactSizeIterator", "Extend",
+    "Default", "PartialEq", "Eq", "PartialOrd", "Ord", "ToOwned", "ToString",
+];
+
+fn is_prelude_trait_path(path: &syn::Path) -> bool {
+    matches!(path.segments.first(), Some(segment) if path.segments.len() == 1 && PRELUDE_TRAITS.iter().any(|name| segment.ident == name))
+}
+
+// paths starting from the crate root, an external crate, or a module relative to the parent, 
+// which don't name an item of the platform module.
+fn is_rooted_path(path: &syn::Path) -> bool {
+    let root = path.segments.first().map(|segment| segment.ident.to_string());
+    path.leading_colon.is_some() || matches!(root.as_deref(), Some("crate" | "self" | "super"))
+}
+
+fn hoist_use_alias(alias: &syn::ItemUse, parent_module: &syn::Path) -> Result<syn::Item, TokenStream2> {
+    let mut hoisted = alias.clone();
+    for parent_segment in parent_module.segments.iter().rev() {
+        hoisted.tree = syn::UseTree::Path(syn::UsePath {
+            ident: parent_segment.ident.clone(),
+            colon2_token: Token![::](alias.span()),
+            tree: Box::new(hoisted.tree)
+        });
+    }
+    // a restricted re-export may go unused in some build configurations, which the 
+    // contract author has no other way to silence.
+    hoisted.attrs.insert(0, syn::parse_quote!(#[allow(unused_imports)]));
+    Ok(syn::Item::Use(hoisted))
+}
+
+// a submodule contract is hoisted to a module of the same name in the parent, whose
+// aliases refer to the matching submodule of the platform module, and whose assertions
+// are checked within it.
+fn hoist_submodule(submodule: &syn::ItemMod, parent_module: &syn::Path) -> Result<syn::Item, TokenStream2> {
+    let items = check_spi_items(submodule)?;
+    let ident = &submodule.ident;
+    let submodule_path: syn::Path = syn::parse_quote!(super::#parent_module::#ident);
+    let (aliases, assertions) = hoist_aliases_and_generate_impls(items, submodule_path)?;
+    let assertions = named_assertions(&assertions);
+
+    let syn::ItemMod { attrs, vis, mod_token, .. } = submodule;
+    Ok(syn::parse_quote! {
+        #(#attrs)*
+        #vis #mod_token #ident {
+            #(#aliases)*
+
+            const _: () = {
+                #(#assertions)*
+            };
+        }
+    })
+}
+
+/// Arguments of the `assert_size!(Type == 16);` contract directive
+struct SizeDirective {
+    ty: syn::Type,
+    _eq: Token![==],
+    size: syn::LitInt
+}
+impl Parse for SizeDirective {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let ty = input.parse()?;
+        let _eq = input.parse()?;
+        let size = match input.parse::<syn::Expr>()? {
+            syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(size), .. }) => size,
+            other => return Err(syn::Error::new(other.span(), "Expected an integer literal size in bytes"))
+        };
+        Ok(SizeDirective { ty, _eq, size })
+    }
+}
+
+// names declared in the parent module by the hoisted aliases, e.g. "PlatformError" for 
+// `pub use platform::ErrorImpl as PlatformError;`
+fn alias_names(aliases: &[syn::Item]) -> Vec<syn::Ident> {
+    aliases.iter().flat_map(declared_names).collect()
+}
+
+// names a hoisted item declares in the parent module
+fn declared_names(alias: &syn::Item) -> Vec<syn::Ident> {
+    fn use_names(tree: &syn::UseTree, names: &mut Vec<syn::Ident>) {
+        match tree {
+            syn::UseTree::Path(path) => use_names(&path.tree, names),
+            syn::UseTree::Name(name) => names.push(name.ident.clone()),
+            syn::UseTree::Rename(rename) => names.push(rename.rename.clone()),
+            syn::UseTree::Group(group) => group.items.iter().for_each(|tree| use_names(tree, names)),
+            syn::UseTree::Glob(_) => {}
+        }
+    }
+
+    let mut names = vec![];
+    match alias {
+        syn::Item::Type(alias) => names.push(alias.ident.clone()),
+        syn::Item::Const(constant) => names.push(constant.ident.clone()),
+        syn::Item::Use(alias) => use_names(&alias.tree, &mut names),
+        syn::Item::Mod(submodule) => names.push(submodule.ident.clone()),
+        _ => {}
+    }
+    names
+}
+
+fn check_declared_alias(ty: &syn::Type, declared: &[syn::Ident]) -> syn::Result<()> {
+    let name = match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => path.segments.first().map(|segment| &segment.ident),
+        _ => None
+    };
+    match name {
+        Some(name) if declared.contains(name) => Ok(()),
+        _ => Err(syn::Error::new(ty.span(), "Expected the name of an alias declared earlier in this SPI module"))
+    }
+}
+
+/// A constant each platform must provide, declared without a value, e.g. `pub const MAX_HANDLES: usize;`
+struct ConstContract {
+    attrs: Vec<syn::Attribute>,
+    vis: syn::Visibility,
+    const_token: Token![const],
+    ident: syn::Ident,
+    colon_token: Token![:],
+    ty: syn::Type,
+    semi_token: Token![;]
+}
+impl Parse for ConstContract {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(ConstContract {
+            attrs: input.call(syn::Attribute::parse_outer)?,
+            vis: input.parse()?,
+            const_token: input.parse()?,
+            ident: input.parse()?,
+            colon_token: input.parse()?,
+            ty: input.parse()?,
+            semi_token: input.parse()?
+        })
+    }
+}
+
+// the hoisted constant is declared with the contract's type, so forwarding a platform 
+// constant of any other type fails to compile. The type is resolved in the parent module.
+fn hoist_const_contract(constant: &ConstContract, parent_module: &syn::Path) -> Result<syn::Item, TokenStream2> {
+    if let syn::Type::Infer(_) | syn::Type::ImplTrait(_) = constant.ty {
+        return Err(syn::Error::new(constant.ty.span(), "Const contracts must name a concrete type").to_compile_error())
+    }
+
+    let ident = &constant.ident;
+    Ok(syn::Item::Const(syn::ItemConst {
+        attrs: constant.attrs.clone(),
+        vis: constant.vis.clone(),
+        const_token: constant.const_token,
+        ident: ident.clone(),
+        generics: Default::default(),
+        colon_token: constant.colon_token,
+        ty: Box::new(constant.ty.clone()),
+        eq_token: Eq(ident.span()),
+        expr: Box::new(syn::parse_quote!(#parent_module::#ident)),
+        semi_token: constant.semi_token
+    }))
+}
+
+// a trait contract names a trait of the platform module rather than declaring one, so 
+// anything beyond the name would be silently ignored.
+fn hoist_trait_reexport(trait_item: &syn::ItemTrait, parent_module: &syn::Path) -> Result<syn::Item, TokenStream2> {
+    let error = if !trait_item.items.is_empty() {
+        Some((trait_item.brace_token.span.join(), "Trait contracts must have an empty body, e.g. 'pub trait Backend {}'"))
+    } else if !trait_item.supertraits.is_empty() {
+        Some((trait_item.supertraits.span(), "Trait contracts cannot declare supertraits"))
+    } else if !trait_item.generics.params.is_empty() || trait_item.generics.where_clause.is_some() {
+        Some((trait_item.generics.span(), "Trait contracts cannot declare generics"))
+    } else {
+        None
+    };
+    if let Some((span, message)) = error {
+        return Err(syn::Error::new(span, message).to_compile_error())
+    }
+
+    let syn::ItemTrait { attrs, vis, ident, .. } = trait_item;
+    Ok(syn::parse_quote! {
+        #[allow(unused_imports)]
+        #(#attrs)*
+        #vis use #parent_module::#ident;
+    })
+}
+
+// a function contract is checked by coercing the platform's function to a function pointer 
+// of the declared signature, which only a function of exactly that signature allows.
+fn hoist_fn_contract(function: &syn::ForeignItemFn, parent_module: &syn::Path) -> Result<Vec<syn::Item>, TokenStream2> {
+    let sig = &function.sig;
+    let error = if !sig.generics.params.is_empty() || sig.generics.where_clause.is_some() {
+        Some((sig.generics.span(), "Function contracts cannot be generic"))
+    } else if sig.asyncness.is_some() {
+        Some((sig.asyncness.span(), "Function contracts cannot be 'async'"))
+    } else if sig.constness.is_some() {
+        Some((sig.constness.span(), "Function contracts cannot be 'const'"))
+    } else if sig.variadic.is_some() {
+        Some((sig.variadic.span(), "Function contracts cannot be variadic"))
+    } else if sig.receiver().is_some() {
+        Some((sig.receiver().span(), "Function contracts cannot take 'self'"))
+    } else {
+        None
+    };
+    if let Some((span, message)) = error {
+        return Err(syn::Error::new(span, message).to_compile_error())
+    }
+
+    let syn::ForeignItemFn { attrs, vis, .. } = function;
+    let syn::Signature { unsafety, abi, ident, inputs, output, .. } = sig;
+    let input_types = inputs.iter().filter_map(|input| match input {
+        syn::FnArg::Typed(typed) => Some(&typed.ty),
+        syn::FnArg::Receiver(_) => None
+    });
+    // spanned at the function's name, so that a mismatch is reported at the contract item.
+    let mut platform_fn = parent_module.clone();
+    platform_fn.segments.iter_mut().for_each(|segment| segment.ident.set_span(ident.span()));
+    platform_fn.segments.push(ident.clone().into());
+
+    let cfgs = cfg_attrs(attrs);
+    Ok(vec![
+        syn::parse_quote! {
+            #[allow(unused_imports)]
+            #(#attrs)*
+            #vis use #parent_module::#ident;
+        },
+        syn::parse_quote! {
+            #(#cfgs)*
+            const _: #unsafety #abi fn(#(#input_types),*) #output = #platform_fn;
+        }
+    ])
+}
+
+// statics are re-exported rather than re-declared, so the parent refers to the same 
+// platform static rather than a copy of it, and their type is checked separately. 
+// Forwarding a `static mut` would need `unsafe` at every use, so it is rejected.
+fn hoist_static_contract(static_item: &syn::ForeignItemStatic, parent_module: &syn::Path) -> Result<Vec<syn::Item>, TokenStream2> {
+    if let syn::StaticMutability::Mut(mutability) = static_item.mutability {
+        return Err(syn::Error::new(mutability.span(), "'static mut' contracts are not supported, use a static with interior mutability instead").to_compile_error())
+    }
+
+    let syn::ForeignItemStatic { attrs, vis, ident, ty, .. } = static_item;
+    let cfgs = cfg_attrs(attrs);
+    Ok(vec![
+        syn::parse_quote! {
+            #[allow(unused_imports)]
+            #(#attrs)*
+            #vis use #parent_module::#ident;
+        },
+        syn::parse_quote! {
+            #(#cfgs)*
+            const _: fn() = || { let _: &'static #ty = &#parent_module::#ident; };
+        }
+    ])
+}
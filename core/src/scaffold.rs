@@ -0,0 +1,34 @@
+//! Creating stub source files for declared targets, e.g. from a build script:
+//! 
+//! ```no_run
+//! platform_spi_core::scaffold::ensure_target_files("src/platform", &["macos", "windows", "linux"])
+//!     .expect("failed to create platform source files");
+//! ```
+
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Creates "{target}.rs" in `dir` for each target without one, containing only a comment naming
+/// the target, and returns the paths of the created files. The directory is created if 
+/// needed, and existing files are never modified.
+pub fn ensure_target_files(dir: impl AsRef<Path>, targets: &[&str]) -> std::io::Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let mut created = vec![];
+    for target in targets {
+        let path = dir.join(format!("{target}.rs"));
+        // create_new fails rather than truncating a file that already exists, even one
+        // written between checking and creating.
+        let mut file = match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => file,
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error)
+        };
+        writeln!(file, "// Platform implementation for the \"{target}\" target.")?;
+        created.push(path);
+    }
+
+    Ok(created)
+}
@@ -0,0 +1,31 @@
+//! Checks the names of the consts that generated assertions are made in.
+
+use platform_spi_core::expand;
+use quote::quote;
+
+fn expanded(content: proc_macro2::TokenStream) -> String {
+    let args = quote! { targets = [linux], unsupported = false };
+    let item = quote! { mod platform { #content } };
+    expand(args, item, None).to_string()
+}
+
+#[test]
+fn type_eq_names_join_generic_types() {
+    let expansion = expanded(quote! {
+        pub type S<T> = SImpl<T>;
+        pub type R = RImpl;
+        assert_type_eq!(S<u8> == R);
+    });
+
+    assert!(expansion.contains("_ASSERT_S_u8_eq_R"), "{expansion}");
+}
+
+#[test]
+fn impl_names_join_bounds() {
+    let expansion = expanded(quote! {
+        pub type PlatformStream = StreamImpl;
+        assert_impl_all!(PlatformStream: Read + Send);
+    });
+
+    assert!(expansion.contains("_ASSERT_PlatformStream_impl_Read_Send"), "{expansion}");
+}
@@ -0,0 +1,17 @@
+//! Checks that stub source files are created only for targets without one.
+
+use platform_spi_core::scaffold::ensure_target_files;
+
+#[test]
+fn missing_target_files_are_created_without_overwriting() {
+    let dir = std::env::temp_dir().join(format!("platform_spi_scaffold_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("linux.rs"), "pub struct ServiceImpl;\n").unwrap();
+
+    let created = ensure_target_files(&dir, &["linux", "macos"]).unwrap();
+
+    assert_eq!(created, vec![dir.join("macos.rs")]);
+    assert_eq!(std::fs::read_to_string(dir.join("linux.rs")).unwrap(), "pub struct ServiceImpl;\n");
+    assert!(ensure_target_files(&dir, &["linux", "macos"]).unwrap().is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,7 +1,7 @@
 use crate::platform::unix::UnixImpl;
 
 pub type FilePathDescriberImpl = UnixImpl;
-pub const OS_NAME: &'static str = "linux";
+pub const OS_NAME: &str = "linux";
 
 #[path = "./maclinuxshared.rs"]
 mod unix;
\ No newline at end of file
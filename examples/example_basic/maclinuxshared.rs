@@ -4,6 +4,6 @@ pub struct UnixImpl;
 
 impl FilePathDescription<String> for UnixImpl {
     fn description(&self) -> String {
-        return "Directories are seperated by /, e.g. example/file/path".to_string();
+        "Directories are seperated by /, e.g. example/file/path".to_string()
     }
 }
\ No newline at end of file
@@ -1,12 +1,12 @@
 use crate::FilePathDescription;
 
 pub type FilePathDescriberImpl = UnsupportedImpl;
-pub const OS_NAME: &'static str = "unknown";
+pub const OS_NAME: &str = "unknown";
 pub struct UnsupportedImpl;
 
 impl ToString for UnsupportedImpl {
     fn to_string(&self) -> String {
-        "This platform is unknown so we do not know how file paths are written.".to_string();
+        "This platform is unknown so we do not know how file paths are written.".to_string()
     }
 }
 
@@ -14,6 +14,6 @@ impl ToString for UnsupportedImpl {
 impl<T> FilePathDescription<String> for T
 where T : ToString {
     fn description(&self) -> String {
-        return self.to_string();
+        self.to_string()
     }
 }
\ No newline at end of file
@@ -1,11 +1,11 @@
 use crate::FilePathDescription;
 
 pub type FilePathDescriberImpl = WindowsImpl;
-pub const OS_NAME: &'static str = "windows";
+pub const OS_NAME: &str = "windows";
 pub struct WindowsImpl;
 
 impl FilePathDescription<String> for WindowsImpl {
     fn description(&self) -> String {
-        return "Directories are seperated by \\, e.g. example\\file\\path".to_string();
+        "Directories are seperated by \\, e.g. example\\file\\path".to_string()
     }
 }
\ No newline at end of file
@@ -1,29 +1,255 @@
+use std::path::{Path, PathBuf};
+
 use proc_macro::TokenStream;
-use proc_macro2::TokenStream as TokenStream2;
-use quote::{quote, quote_spanned, ToTokens};
-use syn::{bracketed, parse::Parse, parse_macro_input, punctuated::Punctuated, spanned::Spanned, token::{Comma, Eq, Semi}, Token};
 
 /// Define a module from a different source file for each named target OS.
 /// 
 /// Each platform-specific implementation must be in a source file named 
 /// "{module_path}/{target_os}.rs". {module_path} is "." by default, but may
-/// be overridden with an optional "module_path" argument to the macro.
+/// be overridden with an optional "module_path" argument to the macro, either as a 
+/// string such as `module_path = "platform/impls"` or a bare path such as 
+/// `module_path = platform::impls`. Paths are resolved relative to the directory of the 
+/// source file containing the macro, even when it is used inside an inline module. Should
+/// the compiler not report the source file, they are instead resolved as `#[path]` 
+/// attributes are, relative to the directory of the enclosing module.
 /// 
 /// ## Aliases
 /// Any "type" and "use" declarations in the module content block will be 
 /// converted into items in the parent module, which refer to items in the target platform
 /// module. These type aliases are the "SPI", required to be implemented
-/// for each supported platform. Additionally, an "impl" declaration can be made to specify 
-/// that each platform type must implement a specific trait.
-/// Item declarations other than "type", "use", and "impl" are not supported.
+/// for each supported platform. A "use" declaration may be any use tree, which is 
+/// resolved within the platform module, e.g. `pub use {ErrorImpl as PlatformError, Config};`
+/// expands to `pub use platform::{ErrorImpl as PlatformError, Config};`. A glob `pub use *;` 
+/// re-exports every public item of the platform module, alongside any explicitly named aliases.
+/// The `reexport_all = true` argument does the same without a contract item.
+/// Hoisted "use" declarations allow `unused_imports`, as a re-export such as `pub(crate) use`
+/// may go unused in some build configurations.
+/// A "type" alias of a path starting with `::`, `crate`, `self`, or `super` is left as written,
+/// resolved from the parent module, as is one already written through the platform module,
+/// e.g. `pub type Stream = platform::StreamImpl;`. Reference, tuple, and array aliases such as 
+/// `pub type Pair = (ServiceImpl, [u8; 64]);` prefix each contained path, leaving primitives alone,
+/// as do function pointer aliases such as `pub type Handler = fn(EventImpl, u32) -> bool;`.
+/// Paths into `std`, `core`, or `alloc`, prelude types such as `Option` and `Vec`, and the 
+/// alias's own type parameters are left as written too, while the type arguments of a standard
+/// type are still prefixed, e.g. `pub type Slots = [Option<SlotImpl>; 2];`. Items of other crates
+/// are named from the root, e.g. `::bytes::Bytes`.
+/// A trait object alias such as `pub type AnyBackend = dyn Backend + Send;` prefixes each trait 
+/// but those of the standard prelude, such as Send, paths into the standard library, such as
+/// `std::fmt::Debug`, and rooted paths.
+/// A projection such as `pub type ReadItem = <ReaderImpl as Iterator>::Item;` prefixes only 
+/// the qualified type, leaving the trait and associated item as written.
+/// 
+/// Additionally, an "impl" declaration can be made to specify 
+/// that each platform type must implement a specific trait, or with a negated 
+/// trait such as `impl !Send for PlatformHandle {}`, that it must not. A generic impl
+/// such as `impl<T: Clone> Codec for PacketCodec<T> {}` requires the trait to be 
+/// implemented for every `T` satisfying the bounds, including any where clause, e.g.
+/// `impl<T> Encode for Wrapper<T> where T: Serialize {}`. Lifetime parameters are checked the
+/// same way, so `impl<'a> Parser<'a> for PlatformParser<'a> {}` requires the impl for every
+/// `'a`. Associated types of the trait can be required too, e.g.
+/// `impl Stream<Error = std::io::Error> for PlatformStream {}`
+/// fails to compile for any platform whose `Stream::Error` is a different type.
+/// An unsafe trait is required with an unsafe impl, e.g. `unsafe impl Sendable for PlatformHandle {}`,
+/// which asserts the impl like any other; the platform's own impl still carries the `unsafe`.
+/// The braces of an "impl" declaration must be empty, a body-less `impl Trait for Type;`
+/// is rejected by rustc before the macro runs. Each assertion is made in a const named 
+/// after it, e.g. `_ASSERT_PlatformService_impl_SomeTrait`, which diagnostics then mention.
+/// 
+/// A "const" declared without a value, e.g. `pub const MAX_HANDLES: usize;`, requires
+/// each platform to provide a constant of that type, which is re-declared in the parent
+/// module with the platform's value. Similarly a "static" declared without a value, 
+/// e.g. `pub static REGISTRY: Registry;`, requires each platform to provide a static of 
+/// that type, which is re-exported from the parent module. `static mut` is not supported.
+/// A "fn" declared without a body, e.g. `pub fn compress(input: u128) -> u64;`, requires each 
+/// platform to provide a function of exactly that signature, which is re-exported from the 
+/// parent module. Parameters must be named, and generic, `async`, and `const` functions are not supported.
+/// A "trait" declared with an empty body, e.g. `pub trait Backend {}`, requires each platform 
+/// to provide a trait of that name, which is re-exported from the parent module.
+/// 
+/// Contract items can be grouped into inline submodules, e.g. `pub mod net { pub type Socket = SocketImpl; }`,
+/// which are hoisted to a submodule of the parent module whose aliases refer to the matching 
+/// submodule of the platform module, e.g. `pub type Socket = super::platform::net::SocketImpl;`.
+/// Paths in a submodule's "impl" declarations are resolved within the hoisted submodule.
+/// Item declarations other than "type", "use", "const", "static", "fn", "trait", "mod", and "impl" are not supported.
+/// With `allow_shared_items = true`, the module may also hold code shared by every platform,
+/// such as a fn with a body, a const with a value, a struct, an inherent impl, or a trait or
+/// trait impl with items, which is emitted unchanged into the parent module alongside the
+/// hoisted contract.
+/// Attributes of a contract item, such as docs, `#[cfg]`, and `#[deprecated]`, are kept on 
+/// the hoisted item. A `#[spi_doc = "..."]` attribute replaces the docs of the hoisted item,
+/// leaving the contract item's own docs as notes for platform implementers. An alias gated
+/// by `#[cfg]`, e.g. `#[cfg(feature = "tls")] pub type TlsStream = TlsStreamImpl;`, also
+/// gates every "impl" declaration, directive, and "require_send_sync" entry naming it.
+/// A `#[cfg]` on an attributed module is evaluated by rustc before the macro runs, so a
+/// disabled module expands to nothing at all. Within [`platform_spi_module!`] the macro sees
+/// the module's `#[cfg]`, e.g. `#[cfg(feature = "plugins")] mod platform { ... }`, which then
+/// applies alongside the selected target's, and gates every hoisted item and assertion too.
+/// 
+/// Thread safety can be required without an "impl" declaration per trait, by listing 
+/// aliases in a "require_send_sync" argument, e.g. `require_send_sync = [PlatformService]` 
+/// requires that each platform's PlatformService implements both Send and Sync.
+/// 
+/// ## Directives
+/// Other assertions about the platform types are made with macro-style directives in 
+/// the module content block:
+/// - `assert_impl_all!(PlatformStream: Read + Send + 'static);` requires that each platform
+///   type satisfies every bound, including lifetime bounds. rustc does not accept several 
+///   traits in a single "impl" declaration, so this directive is the one-line equivalent.
+/// - `assert_impl_any!(PlatformStream: AsyncRead, Read);` requires that each platform 
+///   type implements at least one of the named traits.
+/// - `assert_not_impl_any!(RawHandle: Send, Sync);` requires that each platform type
+///   implements none of the named traits. rustc does not accept `impl !Send + !Sync for RawHandle {}`,
+///   so this directive is the one-line equivalent.
+/// - `assert_type_eq!(PlatformHandle == RawHandle);` requires that the aliases name the
+///   same type on each platform. Each alias must be declared earlier in the module.
+/// - `assert_size!(PlatformTimespec == 16);` requires that the alias has the given size
+///   in bytes on each platform. The alias must be declared earlier in the module.
+/// - `assert_obj_safe!(PlatformBackend);` requires that the trait alias is object safe
+///   on each platform. The alias must be declared earlier in the module.
+///
+/// Assertions are expanded in place to items checked by the type checker alone, with
+/// `::core` paths, so the using crate needs no `static_assertions` dependency, vendored
+/// or otherwise, for them.
 /// 
 /// ## Unsupported Platforms
 /// One additional source file, "unsupported.rs", will be used for attempted compilation 
 /// on any unsupported target platform. Note that it is not necessary to actually 
 /// create unsupported.rs if you never intend to build for an unsupported platform.
+/// The fallback can be omitted entirely with `unsupported = false`, in which case
+/// building for an unsupported platform fails to find the platform module.
+/// With `unsupported = error("this crate supports only macOS, Windows, and Linux")` no
+/// fallback is imported either, and building for an unsupported platform instead fails 
+/// with a `compile_error!` of the given message.
+/// A different fallback file can be named with e.g. `unsupported_path = "fallback.rs"`,
+/// which is resolved relative to "module_path" like the target source files.
+/// 
+/// The fallback need not be a stub: naming a real implementation with e.g. 
+/// `default = "posix.rs"` (equivalent to "unsupported_path") uses it for every platform 
+/// not listed in the targets, such as a long tail of Unix-likes alongside an explicit 
+/// `targets = [windows]`. At least one target must be listed unless a default is named.
+/// A single target may be given without brackets, e.g. `targets = windows`.
+/// With `require_all_targets = true`, every target's source file, and a fallback file named by
+/// "unsupported_path" or "default", must exist when the macro is expanded, on any platform. 
+/// Each missing file is reported at the target naming it. The check is skipped where the 
+/// compiler does not report the invoking source file.
+/// A build script can instead create stubs for any missing files, with 
+/// `platform_spi_core::scaffold::ensure_target_files("src/platform", &["macos", "linux"])`
+/// from the platform-spi-core crate, which never overwrites existing files.
+/// A misspelled OS name such as `windwos` never matches, so the fallback is silently selected instead.
+/// With `strict_targets = true`, each "targets" value must instead be a target_os known to 
+/// rustc, and a misspelling is an error suggesting the closest name. The `os` of a matrix
+/// entry, e.g. `{ arch = x86_64, os = linux }`, is checked too.
+/// A target named by a raw identifier, e.g. `r#move`, selects "move.rs" with `target_os = "move"`.
+/// 
+/// ## Shared Source Files
+/// A single source file may implement several targets, by naming the file and listing
+/// the targets it covers, e.g. `targets = [windows, unix = [macos, linux]]` selects
+/// "unix.rs" with `#[cfg(any(target_os = "macos", target_os = "linux"))]`.
+/// 
+/// A target can be narrowed by further cfg options, which must all be set to select it,
+/// e.g. `targets = [windows, linux(feature = io_uring), linux(feature = epoll)]` selects 
+/// "linux_io_uring.rs" with `#[cfg(all(target_os = "linux", feature = "io_uring"))]`. Where 
+/// targets overlap, such as when both features are enabled, the first listed target is 
+/// selected.
+/// 
+/// A set of targets can be named once in a "groups" argument, and then listed by its name,
+/// e.g. `groups = { unix = [linux, macos, freebsd] }, targets = [windows, unix]` selects 
+/// "unix.rs" on any of the group's targets, as if written `unix = [linux, macos, freebsd]`.
+/// 
+/// A target's source file may also be named explicitly, relative to "module_path",
+/// e.g. `targets = [macos, linux, windows = "win/impl.rs"]`.
+/// 
+/// Where every file follows another naming scheme, a "file_pattern" argument replaces 
+/// "{target}.rs", e.g. `file_pattern = "{target}_impl.rs"` selects "macos_impl.rs" and 
+/// "windows_impl.rs". The default fallback file follows the pattern too, as "unsupported_impl.rs".
+/// Alternatively only the extension may be changed, e.g. `extension = "gen.rs"` selects 
+/// "macos.gen.rs" and "unsupported.gen.rs".
+/// 
+/// ## Single Source File
+/// A module with the same implementation everywhere can still be checked against its 
+/// contract, by naming its only source file with e.g. `#[platform_spi(single = "impl.rs")]`.
+/// The file is imported without any cfg, relative to "module_path", and no targets or 
+/// fallback file may be given.
+/// 
+/// ## Other Selection Keys
+/// Platforms may be selected by something other than operating system, by naming them
+/// with one of the following arguments instead of "targets":
+/// - "arch_targets" selects on `target_arch`, e.g. `arch_targets = [x86_64, aarch64, wasm32]`
+/// - "family_targets" selects on `target_family`, e.g. `family_targets = [unix, windows]`
+/// - "vendor_targets" selects on `target_vendor`, e.g. `vendor_targets = [apple, pc, unknown]`
+/// - "env_targets" selects on `target_env`, e.g. `env_targets = [msvc, gnu]`
+/// - "pointer_width_targets" selects on `target_pointer_width`, e.g. `pointer_width_targets = [32, 64]`
+///   selects "64.rs" with `#[cfg(target_pointer_width = "64")]`
+/// - "endian_targets" selects on `target_endian`, e.g. `endian_targets = [big, little]`
+/// - "feature_targets" selects on Cargo features, e.g. `feature_targets = [real, mock]`
+///   selects "mock.rs" with `#[cfg(feature = "mock")]`
+///
+/// Each source file is then selected with the matching predicate, e.g. "unix.rs" with 
+/// `#[cfg(target_family = "unix")]`, so a single file can cover every Unix-like OS.
+/// 
+/// These arguments may be combined, e.g. `targets = [macos, linux, windows], arch_targets = [wasm = [wasm32]]`
+/// selects "wasm.rs" when building for wasm32, rather than falling back to "unsupported.rs".
+/// Where targets of different arguments overlap, the target listed first is selected.
+/// 
+/// Several keys can also select a single target, by listing them in braces, e.g. 
+/// `targets = [{ os = linux, arch = arm }, { os = linux, arch = aarch64 } => "linux_arm64.rs"]`
+/// selects "linux_arm.rs" with `#[cfg(all(target_os = "linux", target_arch = "arm"))]`. The 
+/// keys are "os", "arch", "family", "vendor", "env", "pointer_width", "endian", and "feature",
+/// and the first names the file, as with `linux(target_arch = arm)`.
+/// 
+/// A target can instead be selected by a cfg predicate written out in full, starting with 
+/// `not`, `any`, or `all`, e.g. `targets = [windows, posix = not(target_os = "windows") => "posix.rs"]`.
+/// The predicate is emitted as written, and the file defaults to the target's name, e.g. "posix.rs".
+/// 
+/// ## cfg-if Backend
+/// Crates depending on the `cfg-if` crate can select the platform module with a single 
+/// `cfg_if::cfg_if!` block instead, by passing `backend = cfg_if`. Each target is then an 
+/// `if #[cfg(...)]` arm in the order listed, and the fallback the final `else` arm.
+/// The default `backend = cfg` needs no dependency.
+/// 
+/// ## Generated Items
+/// With `emit_target_list = true`, the cfg values of the declared targets are listed in 
+/// a generated `pub const SUPPORTED_TARGETS: &[&str]`, e.g. `&["macos", "windows", "linux"]`
+/// for `targets = [macos, windows, linux]`.
+/// 
+/// With `emit_current_target = true`, a generated `pub fn current_target() -> &'static str`
+/// returns the cfg value that selected the platform module, e.g. "linux" when building for
+/// Linux with `targets = [unix = [macos, linux]]`, or "unknown" on an unsupported platform.
+/// 
+/// With `emit_platform_enum = true`, a generated `pub enum Platform` has a variant per cfg 
+/// value in upper camel case, e.g. `Platform::Macos` or `Platform::Target64`, followed by `Platform::Unsupported`, 
+/// and `pub const PLATFORM: Platform` names the platform being built for.
+/// 
+/// Like the hoisted contract, these items are declared in the parent module and gated by 
+/// any `#[cfg]` on the module. Their names are fixed, so each may be emitted by at most one 
+/// enabled invocation per module.
+/// 
+/// A constructor for a contract type can be generated with a "factory" argument naming the 
+/// function, its return type, and the constructor it delegates to, which is either `default`
+/// or the name of an associated function taking no arguments. For example, 
+/// `factory = "backend: PlatformService = default"` generates 
+/// `pub fn backend() -> PlatformService`, returning `PlatformService::default()`, and 
+/// `factory = "backend: PlatformService = new"` returns `PlatformService::new()`.
+/// 
+/// With `default_constructors = true`, each `impl Default for Alias {}` declaration also 
+/// generates a constructor named after the alias in snake case, e.g. 
+/// `pub fn new_platform_service() -> PlatformService` for `impl Default for PlatformService {}`.
+/// 
+/// ## Documentation
+/// Documentation is built for a single platform, whichever rustdoc targets. Passing e.g. 
+/// `doc_target = linux` instead selects the "linux" target whenever `cfg(doc)` is set, so 
+/// that the aliases are always documented from the same implementation. Every other target,
+/// and the fallback, then also requires `not(doc)`, so only one module is ever declared.
+/// 
+/// With `doc_cfg = true`, each hoisted item gated by `#[cfg(...)]` is also annotated with 
+/// `#[doc(cfg(...))]`, so that the documentation shows where it is available. So are the items
+/// of hoisted submodules, and the "reexport_all" re-export of a module gated by `#[cfg]`.
+/// This requires the nightly `doc_cfg` feature to be enabled with `#![feature(doc_cfg)]` in
+/// the crate root, so is only suitable for crates built with a nightly toolchain, such as on
+/// docs.rs.
 /// 
 /// ## Examples
-/// ```
+/// ```ignore
 /// #[platform_spi(targets = [macos, windows, linux])]
 /// mod platform {
 ///     /// A public type alias declared in the parent module. A type named "ServiceImpl<T>" 
@@ -40,21 +266,11 @@ use syn::{bracketed, parse::Parse, parse_macro_input, punctuated::Punctuated, sp
 /// 
 /// is equivalent to
 /// 
-/// ```
-/// #[cfg(target_os = "macos")]
-/// #[path = "./macos.rs"]
-/// mod platform;
-/// 
-/// #[cfg(target_os = "windows")]
-/// #[path = "./windows.rs"]
-/// mod platform;
-/// 
-/// #[cfg(target_os = "linux")]
-/// #[path = "./linux.rs"]
-/// mod platform;
-/// 
-/// #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
-/// #[path = "./unsupported.rs"]
+/// ```ignore
+/// #[cfg_attr(target_os = "macos", path = "macos.rs")]
+/// #[cfg_attr(target_os = "windows", path = "windows.rs")]
+/// #[cfg_attr(target_os = "linux", path = "linux.rs")]
+/// #[cfg_attr(not(any(target_os = "macos", target_os = "windows", target_os = "linux")), path = "unsupported.rs")]
 /// mod platform;
 /// 
 /// #[doc = "Public type alias to the platform-specific implementation of trait Service<T>."]
@@ -62,210 +278,242 @@ use syn::{bracketed, parse::Parse, parse_macro_input, punctuated::Punctuated, sp
 /// #[doc = "A platform-specific error type, renamed and exported from the parent module as \"PlatformError\"."]
 /// pub use platform::ErrorImpl as PlatformError;
 /// 
-/// static_assertions::assert_impl_all!(PlatformService : SomeTrait);
+/// const _: () = {
+///     const _ASSERT_PlatformService_impl_SomeTrait: () = {
+///         const _: fn() = || {
+///             fn assert_impl_all<T: ?Sized + SomeTrait>() {}
+///             assert_impl_all::<PlatformService>();
+///         };
+///     };
+/// };
 /// ```
 #[proc_macro_attribute]
 pub fn platform_spi(args: TokenStream, item: TokenStream) -> TokenStream {
-
-    let config = parse_macro_input!(args as SpiAttributes);
-
-    let mod_decl = parse_macro_input!(item as syn::ItemMod);
-    let rewritten_decl = match SpiModule::try_from(&mod_decl) {
-        Ok(module) => module,
-        Err(diagnostics) => return diagnostics,
-    };
-
-    // the inline module declaration, rewritten as module file import.
-    let mod_import = &rewritten_decl.mod_import_decl;
-
-    let target_names: Vec<String> = config.target_names();
-    let mod_paths: Vec<String> = config.source_paths();
-
-    // SPI type aliases hoisted from the module declaration.
-    let aliases = &rewritten_decl.aliases;
-
-    let (types, impls) = &rewritten_decl.implementations;
-
-    quote! {
-        #( 
-            #[cfg(target_os = #target_names)]
-            #[path = #mod_paths]
-            #mod_import
-        )*
-
-        #[cfg(not(any(#( target_os = #target_names ),*)))]
-        #[path = "./unsupported.rs"]
-        #mod_import
-
-        #(#aliases)*
-
-        #(static_assertions::assert_impl_all!(#types : #impls);)*
-    }.into()
-
-}
-
-struct SpiAttributes {
-    targets: Punctuated::<syn::Ident, Comma>,
-    module_path: syn::LitStr
+    platform_spi_core::expand(args.into(), item.into(), invoking_source_dir()).into()
 }
-impl SpiAttributes {
-    // string literals naming each module source file, e.g. "./macos.rs"
-    fn source_paths(&self) -> Vec<String> {
-        self.targets.iter().map(
-            |id| format!("{}/{id}.rs", self.module_path.value())
-        ).collect()
-    }
 
-    /// string literals naming each target_os value, e.g. "macos"
-    fn target_names(&self) -> Vec<String> {
-        self.targets.iter().map(syn::Ident::to_string).collect()
-    }
-}
-impl Parse for SpiAttributes {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let mut result = SpiAttributes {
-            module_path: syn::LitStr::new(".", input.span()),
-            targets: Default::default()
-        };
-
-        while !input.is_empty() {
-
-            let name = syn::Ident::parse(&input)?;
-            let _eq: Eq = input.parse()?;
-
-            match name.to_string().as_str() {
-                "targets" => {
-                    let targets;
-                    let _bracket = bracketed!(targets in input);
-                    result.targets = targets.parse_terminated(syn::Ident::parse, Comma)?;
-                },
-                "module_path" => {
-                    result.module_path = input.parse()?
-                },
-                _ => return Err(input.error(format!("Unexpected attribute '{name}'")))
-            }
-
-            let _comma = input.lookahead1();
-            if _comma.peek(Comma) {
-                let _comma: Comma = input.parse()?;
-            }
-        }
-
-        Ok(result)
-    }
-}
-
-struct SpiModule {
-    mod_import_decl: syn::ItemMod,
-    aliases: Vec<syn::Item>,
-    implementations: (Vec<syn::Type>, Vec<syn::Path>)
-}
-// implementing TryFrom rather than Parse allows us to reuse most of the parse logic
-// from ItemMod, plus be a little more fine-grained with errors (e.g. we can report 
-// multiple errors, limit our errors to specific spans).
-impl TryFrom<&syn::ItemMod> for SpiModule {
-    type Error = TokenStream;
-
-    fn try_from(mod_decl: &syn::ItemMod) -> Result<Self, Self::Error> {
-        let parent_module = mod_decl.ident.clone();
-
-        let mod_aliases = check_spi_items(mod_decl)?;
-        let (aliases, implementations) = hoist_aliases_and_generate_impls(mod_aliases, parent_module)?;
-
-        let mod_import_decl = syn::ItemMod {
-            attrs: mod_decl.attrs.clone(),
-            vis: mod_decl.vis.clone(),
-            unsafety: mod_decl.unsafety.clone(),
-            mod_token: mod_decl.mod_token,
-            ident: mod_decl.ident.clone(),
-            content: None,
-            semi: Some(Semi(mod_decl.ident.span())),
-        };
-
-        Ok(Self { mod_import_decl, aliases, implementations})
-    }
-}
-
-fn check_spi_items(mod_decl: &syn::ItemMod) -> Result<&[syn::Item], TokenStream> {
-    match &mod_decl.content {
-        Some((_, content)) => 
-            Ok(content),
-        None => 
-            Err(quote_spanned! {
-                mod_decl.ident.span() => 
-                    compile_error!("External module imports are not supported, only inline module declarations.")
-            }.to_token_stream().into())
-    }
-}
-
-fn hoist_aliases_and_generate_impls(mod_aliases: &[syn::Item], parent_module: syn::Ident) -> Result<(Vec<syn::Item>, (Vec<syn::Type>, Vec<syn::Path>)), TokenStream> {
-    let mut invalid_items: Vec<TokenStream2> = vec![];
-    let mut aliases: Vec<syn::Item> = vec![];
-    let mut impl_types: Vec<syn::Type> = vec![];
-    let mut impls: Vec<syn::Path> = vec![];
-
-    for item in mod_aliases {
-        if let syn::Item::Impl(impl_item) = item {
-            if let (0, None, Some((None, path, _))) = (impl_item.items.len(), &impl_item.generics.where_clause, &impl_item.trait_) {
-                impl_types.push(*impl_item.self_ty.clone());
-                impls.push(path.clone());
-            } else {
-                invalid_items.push(quote_spanned! {
-                    item.span() => compile_error!("Impl block is incorrectly formed, only format of 'impl Trait for Type {}' is allowed")
-                });
-            }
-            continue;
-        }
-        let hoisted = match item {
-            syn::Item::Type(alias) => hoist_type_alias(alias, &parent_module),
-            syn::Item::Use(alias) => hoist_use_alias(alias, &parent_module),
-            _ => Err(quote_spanned! {
-                item.span() => compile_error!("Only 'type', 'use', and 'impl' items are supported in an SPI module declaration but found")
-            })
-        };
-        match hoisted {
-            Ok(item) => aliases.push(item),
-            Err(diagnostic) => invalid_items.push(diagnostic),
-        }
-    }
-
-    if !invalid_items.is_empty() {
-        let collected: TokenStream2 = invalid_items.into_iter().collect();
-        return Err(collected.into())
-    }
-
-    Ok((aliases, (impl_types, impls)))
+/// The function-like form of [`macro@platform_spi`], for generating a contract from another
+/// macro. The attribute's arguments are followed by `;` and the inline module, e.g.
+/// 
+/// ```ignore
+/// platform_spi_module! {
+///     targets = [macos, windows, linux];
+/// 
+///     mod platform {
+///         pub type PlatformService = ServiceImpl;
+///     }
+/// }
+/// ```
+/// 
+/// expands exactly as `#[platform_spi(targets = [macos, windows, linux])]` on the module. The 
+/// attribute's name is already taken in the macro namespace, so this form has its own.
+#[proc_macro]
+pub fn platform_spi_module(input: TokenStream) -> TokenStream {
+    platform_spi_core::expand_inline(input.into(), invoking_source_dir()).into()
 }
 
-fn hoist_type_alias(alias: &syn::ItemType, parent_module: &syn::Ident) -> Result<syn::Item, TokenStream2> {
-    match alias.ty.as_ref() {
-        syn::Type::Path(type_path) => {
-            let parent_path = syn::PathSegment {
-                ident: parent_module.clone(),
-                arguments: syn::PathArguments::None
-            };
-            let mut hoisted_path = type_path.clone();
-            hoisted_path.path.segments.insert(0, parent_path);
-
-            let mut hoisted = alias.clone();
-            hoisted.ty = Box::new(syn::Type::Path(hoisted_path));
-
-            Ok(syn::Item::Type(hoisted))
-        },
-        _ => {
-            Err(quote_spanned! {
-                alias.span() => compile_error!("Only path aliases are supported in an SPI module declaration")
-            })
-        }
-    }
+// the absolute directory of the file containing the macro invocation, or None if the 
+// invocation is not in a file on the local filesystem.
+fn invoking_source_dir() -> Option<PathBuf> {
+    let file = proc_macro::Span::call_site().local_file()?;
+    let file = std::path::absolute(file).ok()?;
+    file.parent().map(Path::to_path_buf)
 }
 
-fn hoist_use_alias(alias: &syn::ItemUse, parent_module: &syn::Ident) -> Result<syn::Item, TokenStream2> {
-    let mut hoisted = alias.clone();
-    hoisted.tree = syn::UseTree::Path(syn::UsePath {
-        ident: parent_module.clone(),
-        colon2_token: Token![::](alias.span()),
-        tree: Box::new(hoisted.tree)
-    });
-    Ok(syn::Item::Use(hoisted))
+// contracts that must fail to compile, which rustdoc checks for the expected error as there 
+// is no other compile-fail harness. Paths are resolved from this file's directory.
+#[cfg(doctest)]
+mod compile_fail {
+    /// An impl contract on a concrete instantiation of a generic alias is asserted for that 
+    /// instantiation, so fails where only another instantiation implements the trait.
+    /// ```compile_fail,E0277
+    /// use platform_spi::platform_spi;
+    ///
+    /// pub trait Codec {
+    ///     fn encode(&self) -> u8;
+    /// }
+    ///
+    /// #[platform_spi(module_path = "../tests/impls", family_targets = [host = [unix, windows]], unsupported = false)]
+    /// mod platform {
+    ///     pub type PlatformWrapper<T> = WrapperImpl<T>;
+    ///
+    ///     impl Codec for PlatformWrapper<u16> {}
+    /// }
+    /// # fn main() {}
+    /// ```
+    struct ConcreteInstantiation;
+
+    /// `unsupported = error(...)` fails the build on a platform matching none of the targets.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/impls", targets = [plan9], unsupported = error("only plan9 is supported"))]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct UnsupportedError;
+
+    /// A function contract is checked by coercing the platform's function to a function 
+    /// pointer of the declared signature, so fails where the platform's returns another type.
+    /// ```compile_fail,E0308
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/hoisting", family_targets = [host = [unix, windows]], unsupported = false)]
+    /// mod platform {
+    ///     pub fn parse_port(input: &str) -> Option<u32>;
+    /// }
+    /// # fn main() {}
+    /// ```
+    struct FnContractSignature;
+
+    /// An impl contract in a nested submodule is asserted for the submodule's platform type.
+    /// ```compile_fail,E0277
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/hoisting", family_targets = [host = [unix, windows]], unsupported = false)]
+    /// mod platform {
+    ///     pub mod io {
+    ///         pub mod net {
+    ///             pub mod tcp {
+    ///                 pub type Tcp = TcpImpl;
+    ///
+    ///                 impl Copy for Tcp {}
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// # fn main() {}
+    /// ```
+    struct SubmoduleImplContract;
+
+    /// With `require_all_targets = true`, a missing source file fails the build even for a
+    /// target other than the one being built for.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/common", targets = [redox], family_targets = [host = [unix, windows]], unsupported = false, require_all_targets = true)]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct RequireAllTargets;
+
+    /// Each group may only be defined once.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/common", groups = { host = [unix], host = [windows] }, family_targets = [host], unsupported = false)]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct GroupDefinedTwice;
+
+    /// Each key of a matrix target must be one of the selection keys.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/common", targets = [{ family = unix, cpu = x86_64 } => "host.rs"], unsupported = false)]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct MatrixUnknownKey;
+
+    /// Each key of a matrix target may only be given once.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/common", targets = [{ family = unix, family = windows } => "host.rs"], unsupported = false)]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct MatrixKeyGivenTwice;
+
+    /// A matrix target needs at least one key.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/common", targets = [{} => "host.rs"], unsupported = false)]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct MatrixWithoutKeys;
+
+    /// A predicate target must start with `not`, `any`, or `all`.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/common", targets = [host = maybe(unix)], unsupported = false)]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct PredicateOperator;
+
+    /// The "doc_target" argument must name a listed target.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/common", family_targets = [host = [unix, windows]], doc_target = linux, unsupported = false)]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct DocTargetNotListed;
+
+    /// With `strict_targets = true`, a misspelled target_os is an error suggesting "windows".
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/common", strict_targets = true, targets = [windwos], default = "host.rs")]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct StrictTargetsMisspelling;
+
+    /// With `strict_targets = true`, the `os` of a matrix entry is checked too.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/common", strict_targets = true, targets = [{ arch = x86_64, os = linx } => "host.rs"], default = "host.rs")]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct StrictTargetsMatrixMisspelling;
+
+    /// The "file_pattern" argument must contain `{target}`.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/common", file_pattern = "impl.rs", family_targets = [host = [unix, windows]], unsupported = false)]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct FilePatternWithoutTarget;
+
+    /// The "extension" argument is given without a leading '.'.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/common", extension = ".rs", family_targets = [host = [unix, windows]], unsupported = false)]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct ExtensionWithLeadingDot;
+
+    /// A "single" source file is imported everywhere, so no targets may be given.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(module_path = "../tests/common", single = "host.rs", family_targets = [host = [unix, windows]])]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct SingleWithTargets;
+
+    /// With `backend = cfg_if`, the error is the final `else` arm.
+    /// ```compile_fail
+    /// use platform_spi::platform_spi;
+    ///
+    /// #[platform_spi(backend = cfg_if, module_path = "../tests/common", targets = [redox], unsupported = error("only redox is supported"))]
+    /// mod platform {}
+    /// # fn main() {}
+    /// ```
+    struct CfgIfUnsupportedError;
 }
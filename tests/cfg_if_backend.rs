@@ -0,0 +1,35 @@
+//! Checks that `backend = cfg_if` selects the platform module through cfg_if! arms.
+// the target and else arms of separate invocations resolve to the same shared fixture.
+#![allow(clippy::duplicate_mod)]
+
+use platform_spi::platform_spi;
+
+// the "redox" file does not exist, so building fails if its doc arm is taken outside rustdoc
+// or its arm is taken ahead of the host's.
+#[platform_spi(
+    backend = cfg_if,
+    module_path = "common",
+    targets = [redox],
+    family_targets = [host = [unix, windows]],
+    doc_target = redox,
+    unsupported = false
+)]
+mod platform {
+    pub use NAME as TARGET_NAME;
+}
+
+// no target matches, so the fallback of the else arm is selected.
+#[platform_spi(backend = cfg_if, module_path = "common", targets = [redox], default = "host.rs")]
+mod fallback {
+    pub use NAME as FALLBACK_NAME;
+}
+
+#[test]
+fn target_arm_is_selected() {
+    assert_eq!(TARGET_NAME, "host");
+}
+
+#[test]
+fn else_arm_is_selected() {
+    assert_eq!(FALLBACK_NAME, "host");
+}
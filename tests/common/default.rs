@@ -0,0 +1,6 @@
+use platform_spi::platform_spi;
+
+#[platform_spi(family_targets = [host = [unix, windows]], unsupported = false)]
+mod platform {
+    pub use NAME as DEFAULT_NAME;
+}
@@ -0,0 +1,2 @@
+/// Selected in place of host.rs by `extension = "gen.rs"`.
+pub const NAME: &str = "generated";
@@ -0,0 +1,2 @@
+/// Re-exported by contracts that only need to find their platform module.
+pub const NAME: &str = "host";
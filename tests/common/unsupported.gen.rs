@@ -0,0 +1,2 @@
+/// Selected as the default fallback file by `extension = "gen.rs"`.
+pub const NAME: &str = "unsupported generated";
@@ -0,0 +1,2 @@
+/// Selected by contracts with no target matching, as the default fallback file.
+pub const NAME: &str = "unsupported";
@@ -0,0 +1,219 @@
+//! Checks that contract items are hoisted into the parent module intact.
+#![deny(missing_docs)]
+
+use platform_spi::platform_spi;
+
+#[platform_spi(module_path = "hoisting", family_targets = [host = [unix, windows]], unsupported = false)]
+mod platform {
+    /// A type alias whose docs must survive hoisting.
+    pub type DocumentedType = TypeImpl;
+
+    /// A constant whose docs must survive hoisting.
+    pub const DOCUMENTED_CONST: u32;
+
+    /// A function re-exported once its signature is checked against the platform's.
+    pub fn parse_port(input: &str) -> Option<u16>;
+
+    pub use {ErrorImpl as PlatformError, ConfigImpl as PlatformConfig, Plain};
+
+    pub use SocketImpl;
+
+    pub use io::net::{tcp::TcpImpl as Tcp, udp::UdpImpl as Udp};
+
+    /// Socket types, hoisted to a submodule of the same path.
+    pub mod io {
+        /// The platform's sockets, hoisted two levels deep.
+        pub mod net {
+            /// The platform's UDP socket, resolved within its submodule.
+            pub type Udp = udp::UdpImpl;
+
+            /// The platform's TCP socket, with a contract checked within its submodule.
+            pub mod tcp {
+                /// The platform's TCP socket.
+                pub type Tcp = TcpImpl;
+
+                impl Default for Tcp {}
+            }
+        }
+    }
+
+    /// A generic alias whose parameters must survive hoisting.
+    pub type PlatformService<T> = ServiceImpl<T>;
+
+    /// A deprecated alias, which must stay deprecated once hoisted.
+    #[deprecated(note = "use DocumentedType")]
+    pub type DeprecatedType = TypeImpl;
+
+    /// An associated type projection, resolved from the platform's reader.
+    pub type ReadItem = <ReaderImpl as Iterator>::Item;
+
+    /// A trait object of the platform's backend, which must also be thread safe.
+    pub type AnyBackend = dyn Backend + Send;
+
+    /// A trait object of a standard library trait outside the prelude.
+    pub type AnyDebug = dyn std::fmt::Debug + Send;
+
+    /// A tuple of a platform type and a standard library type.
+    pub type Timed = (TypeImpl, std::time::Duration);
+
+    /// An array of prelude types wrapping a platform type.
+    pub type Slots = [Option<TypeImpl>; 2];
+
+    /// A reference to a prelude type wrapping a platform type.
+    pub type Borrowed<'a> = &'a Vec<TypeImpl>;
+
+    /// A function pointer returning a prelude type wrapping platform and std types.
+    pub type Parser = fn(&str) -> Result<TypeImpl, std::num::ParseIntError>;
+
+    /// A function pointer taking a platform type and returning a prelude type.
+    pub type Lookup = fn(TypeImpl) -> Option<u8>;
+
+    /// A prelude type of the alias's own type parameter, which is not prefixed.
+    pub type Maybe<T> = Option<T>;
+
+    /// An alias already written through the platform module, which is not prefixed again.
+    pub type QualifiedType = platform::TypeImpl;
+
+    /// Fails to resolve unless the cfg reaches the hoisted alias.
+    #[cfg(any())]
+    pub type DisabledType = MissingImpl;
+
+    /// Fails to resolve unless the cfg reaches the hoisted re-export.
+    #[cfg(any())]
+    pub use MissingImpl as DisabledUse;
+}
+
+// building under deny(missing_docs) fails unless the docs reach the hoisted items.
+#[test]
+fn doc_attributes_are_hoisted() {
+    assert_eq!(DOCUMENTED_CONST, 7);
+    assert_eq!(DocumentedType::default(), platform::TypeImpl);
+}
+
+#[test]
+fn fn_contract_is_hoisted() {
+    assert_eq!(parse_port("8080"), Some(8080));
+    assert_eq!(parse_port("port"), None);
+}
+
+#[test]
+fn use_group_is_hoisted() {
+    assert_eq!(PlatformError::NAME, "error");
+    assert_eq!(PlatformConfig::NAME, "config");
+    assert_eq!(Plain::NAME, "plain");
+}
+
+#[test]
+fn plain_use_is_hoisted() {
+    assert_eq!(SocketImpl::NAME, "socket");
+}
+
+#[test]
+fn nested_use_tree_is_hoisted() {
+    assert_eq!(Tcp::NAME, "tcp");
+    assert_eq!(Udp::NAME, "udp");
+}
+
+#[test]
+fn nested_submodule_contracts_are_hoisted() {
+    assert_eq!(io::net::Udp::NAME, "udp");
+    assert_eq!(io::net::tcp::Tcp::NAME, "tcp");
+    let _: io::net::tcp::Tcp = Default::default();
+}
+
+#[test]
+fn generic_type_alias_is_hoisted() {
+    fn service<T>(value: T) -> PlatformService<T> {
+        platform::ServiceImpl(value)
+    }
+
+    assert_eq!(service(3u8).0, 3);
+}
+
+#[test]
+fn qualified_self_alias_is_hoisted() {
+    let item: ReadItem = platform::ReaderImpl.next().unwrap();
+    assert_eq!(item, 42u16);
+}
+
+#[test]
+fn trait_object_alias_is_hoisted() {
+    let backend: Box<AnyBackend> = Box::new(platform::TypeImpl);
+    assert_eq!(backend.name(), "host");
+}
+
+#[test]
+fn std_trait_object_alias_is_not_prefixed() {
+    let debug: Box<AnyDebug> = Box::new(platform::TypeImpl);
+    assert_eq!(format!("{debug:?}"), "TypeImpl");
+}
+
+#[test]
+fn std_types_in_compound_aliases_are_not_prefixed() {
+    let timed: Timed = (platform::TypeImpl, std::time::Duration::from_secs(1));
+    let slots: Slots = [Some(timed.0), None];
+    let boxed = vec![platform::TypeImpl];
+    let borrowed: Borrowed<'_> = &boxed;
+    let maybe: Maybe<u8> = Some(1);
+
+    assert_eq!(slots[0], Some(platform::TypeImpl));
+    assert_eq!(borrowed.len(), 1);
+    assert_eq!(maybe, Some(1));
+}
+
+#[test]
+fn fn_pointer_aliases_return_prelude_types() {
+    fn parse(input: &str) -> Result<platform::TypeImpl, std::num::ParseIntError> {
+        input.parse::<u8>().map(|_| platform::TypeImpl)
+    }
+    fn lookup(_: platform::TypeImpl) -> Option<u8> {
+        Some(7)
+    }
+
+    let parser: Parser = parse;
+    let lookup: Lookup = lookup;
+    assert_eq!(parser("1"), Ok(platform::TypeImpl));
+    assert!(parser("x").is_err());
+    assert_eq!(lookup(platform::TypeImpl), Some(7));
+}
+
+#[test]
+fn platform_qualified_alias_is_not_prefixed_twice() {
+    assert_eq!(QualifiedType::default(), platform::TypeImpl);
+}
+
+#[test]
+#[allow(deprecated)]
+fn attributes_are_hoisted() {
+    assert_eq!(DeprecatedType::default(), platform::TypeImpl);
+}
+
+mod common {
+    pub struct CommonThing;
+}
+
+pub(crate) struct OuterThing;
+
+mod rooted {
+    use platform_spi::platform_spi;
+
+    pub struct LocalThing;
+
+    // rooted aliases name no items of the platform module, so the shared fixture goes unused.
+    #[platform_spi(module_path = "common", family_targets = [host = [unix, windows]], unsupported = false)]
+    #[allow(dead_code)]
+    mod platform {
+        pub type AbsoluteRooted = ::core::primitive::u32;
+        pub type CrateRooted = crate::common::CommonThing;
+        pub type SelfRooted = self::LocalThing;
+        pub type SuperRooted = super::OuterThing;
+    }
+}
+
+#[test]
+fn rooted_type_aliases_are_not_prefixed() {
+    let _: rooted::AbsoluteRooted = 0u32;
+    let _: rooted::CrateRooted = common::CommonThing;
+    let _: rooted::SelfRooted = rooted::LocalThing;
+    let _: rooted::SuperRooted = OuterThing;
+}
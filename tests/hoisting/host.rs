@@ -0,0 +1,88 @@
+#[derive(Debug, Default, PartialEq)]
+pub struct TypeImpl;
+
+pub const DOCUMENTED_CONST: u32 = 7;
+
+/// Checked against the signature of a function contract.
+pub fn parse_port(input: &str) -> Option<u16> {
+    input.parse().ok()
+}
+
+/// Re-exported by a renaming use group.
+pub struct ErrorImpl;
+impl ErrorImpl {
+    /// Identifies the re-exported type.
+    pub const NAME: &'static str = "error";
+}
+
+/// Re-exported by a renaming use group.
+pub struct ConfigImpl;
+impl ConfigImpl {
+    /// Identifies the re-exported type.
+    pub const NAME: &'static str = "config";
+}
+
+/// Re-exported under its own name by a use group.
+pub struct Plain;
+impl Plain {
+    /// Identifies the re-exported type.
+    pub const NAME: &'static str = "plain";
+}
+
+/// Re-exported under its own name.
+pub struct SocketImpl;
+impl SocketImpl {
+    /// Identifies the re-exported type.
+    pub const NAME: &'static str = "socket";
+}
+
+/// Aliased with its generic parameter.
+pub struct ServiceImpl<T>(pub T);
+
+/// Projected onto by a qualified-self alias.
+pub struct ReaderImpl;
+impl Iterator for ReaderImpl {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        Some(42)
+    }
+}
+
+/// Named with Send by a trait object alias.
+pub trait Backend {
+    /// Identifies the implementing platform.
+    fn name(&self) -> &'static str;
+}
+impl Backend for TypeImpl {
+    fn name(&self) -> &'static str {
+        "host"
+    }
+}
+
+/// Re-exported through a use tree with path segments before and within its group.
+pub mod io {
+    /// The socket implementations.
+    pub mod net {
+        /// The TCP socket implementation.
+        pub mod tcp {
+            /// Re-exported as Tcp.
+            #[derive(Default)]
+            pub struct TcpImpl;
+            impl TcpImpl {
+                /// Identifies the re-exported type.
+                pub const NAME: &'static str = "tcp";
+            }
+        }
+
+        /// The UDP socket implementation.
+        pub mod udp {
+            /// Re-exported as Udp.
+            pub struct UdpImpl;
+            impl UdpImpl {
+                /// Identifies the re-exported type.
+                pub const NAME: &'static str = "udp";
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Checks that generated code resolves standard items by absolute paths, unaffected by
+//! local items shadowing their names.
+#![allow(dead_code, non_camel_case_types)]
+
+use platform_spi::platform_spi;
+
+mod core {}
+mod static_assertions {}
+struct Option;
+struct Some;
+struct None;
+struct PhantomData;
+trait Send {}
+trait Sync {}
+trait Default {}
+trait Deref {}
+#[allow(unused_macros)]
+macro_rules! assert {
+    ($($tokens:tt)*) => { compile_error!("the local assert! macro was used") };
+}
+
+#[platform_spi(
+    module_path = "no_std",
+    family_targets = [host = [unix, windows]],
+    unsupported = false,
+    require_send_sync = [PlatformDevice],
+    default_constructors = true,
+    factory = "device: PlatformDevice = default",
+    emit_platform_enum = true,
+    emit_current_target = true
+)]
+mod platform {
+    pub type PlatformDevice = DeviceImpl;
+
+    impl ::core::default::Default for PlatformDevice {}
+
+    pub trait Device {}
+
+    assert_impl_any!(PlatformDevice: ::core::clone::Clone, ::core::default::Default);
+    assert_size!(PlatformDevice == 4);
+    assert_obj_safe!(Device);
+}
+
+#[test]
+fn generated_items_ignore_shadowed_names() {
+    assert_eq!(device().id, new_platform_device().id);
+    let _: Platform = PLATFORM;
+    assert_ne!(current_target(), "");
+}
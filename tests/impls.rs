@@ -0,0 +1,102 @@
+//! Checks that impl contracts expand to assertions that hold for each platform type.
+
+use platform_spi::platform_spi;
+
+/// Implemented by each platform's parser for every borrowed input lifetime.
+pub trait Parser<'a> {
+    fn parse(&self) -> &'a str;
+}
+
+/// Implemented by each platform's handle, which vouches that it may cross threads.
+///
+/// # Safety
+/// The handle must not be tied to the thread that created it.
+pub unsafe trait Sendable {}
+
+/// Implemented by each platform's wrapper only for a concrete instantiation.
+pub trait Codec {
+    fn encode(&self) -> u8;
+}
+
+#[platform_spi(module_path = "impls", family_targets = [host = [unix, windows]], unsupported = false)]
+mod platform {
+    pub type PlatformWidget = WidgetImpl;
+
+    impl Default for PlatformWidget {}
+
+    impl Clone for PlatformWidget {
+    }
+
+    pub type PlatformWrapper<T> = WrapperImpl<T>;
+
+    impl Default for PlatformWrapper::<u8> {}
+
+    impl AsRef<[u8]> for PlatformWrapper::<u8> {}
+
+    impl<T: Clone> From<T> for PlatformWrapper::<T> {}
+
+    impl Codec for PlatformWrapper<u8> {}
+
+    pub type PlatformParser<'a> = ParserImpl<'a>;
+
+    impl<'a> Parser<'a> for PlatformParser<'a> {}
+
+    pub type PlatformHandle = HandleImpl;
+
+    unsafe impl Sendable for PlatformHandle {}
+}
+
+// a second invocation in the same module generates identically named assertion helpers,
+// which must stay scoped to their anonymous consts.
+#[platform_spi(module_path = "impls", family_targets = [other = [unix, windows]], unsupported = false)]
+mod other_platform {
+    pub type OtherWidget = WidgetImpl;
+
+    impl Default for OtherWidget {}
+
+    impl Clone for OtherWidget {}
+}
+
+// the contract is checked at compile time, so building is the assertion.
+#[test]
+fn empty_brace_impl_contracts_hold() {
+    let widget = PlatformWidget::default();
+    let _ = widget.clone();
+}
+
+#[test]
+fn repeated_invocations_do_not_collide() {
+    let widget = OtherWidget::default();
+    let _ = widget.clone();
+}
+
+#[test]
+fn turbofish_self_types_are_asserted() {
+    let wrapper = PlatformWrapper::<u8>::default();
+    assert_eq!(wrapper.as_ref(), &[0]);
+    assert_eq!(PlatformWrapper::from('x').0, 'x');
+}
+
+#[test]
+fn concrete_instantiations_are_asserted() {
+    fn encode<T: Codec>(value: T) -> u8 {
+        value.encode()
+    }
+
+    assert_eq!(encode(PlatformWrapper::<u8>::from(9)), 9);
+}
+
+#[test]
+fn lifetime_generic_impls_are_asserted() {
+    let input = String::from("input");
+    assert_eq!(PlatformParser::from(input.as_str()).parse(), "input");
+}
+
+#[test]
+fn unsafe_trait_impls_are_asserted() {
+    fn send<T: Sendable>(value: T) -> T {
+        value
+    }
+
+    assert_eq!(send(PlatformHandle::default()).0, 0);
+}
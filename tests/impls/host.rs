@@ -0,0 +1,43 @@
+#[derive(Clone, Default)]
+pub struct WidgetImpl;
+
+#[derive(Default)]
+pub struct WrapperImpl<T>(pub T);
+
+impl AsRef<[u8]> for WrapperImpl<u8> {
+    fn as_ref(&self) -> &[u8] {
+        core::slice::from_ref(&self.0)
+    }
+}
+
+impl<T> From<T> for WrapperImpl<T> {
+    fn from(value: T) -> Self {
+        WrapperImpl(value)
+    }
+}
+
+impl crate::Codec for WrapperImpl<u8> {
+    fn encode(&self) -> u8 {
+        self.0
+    }
+}
+
+pub struct ParserImpl<'a>(pub &'a str);
+
+impl<'a> From<&'a str> for ParserImpl<'a> {
+    fn from(input: &'a str) -> Self {
+        ParserImpl(input)
+    }
+}
+
+impl<'a> crate::Parser<'a> for ParserImpl<'a> {
+    fn parse(&self) -> &'a str {
+        self.0
+    }
+}
+
+#[derive(Default)]
+pub struct HandleImpl(pub i32);
+
+// SAFETY: the handle is a plain integer with no thread affinity.
+unsafe impl crate::Sendable for HandleImpl {}
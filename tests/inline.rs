@@ -0,0 +1,23 @@
+//! Checks that the function-like macro expands as the attribute does, including from 
+//! within another macro.
+
+use platform_spi::platform_spi_module;
+
+macro_rules! host_contract {
+    ($name:ident) => {
+        platform_spi_module! {
+            module_path = "common", family_targets = [host = [unix, windows]], unsupported = false;
+
+            mod platform {
+                pub use NAME as $name;
+            }
+        }
+    };
+}
+
+host_contract!(INLINE_NAME);
+
+#[test]
+fn contract_generated_by_macro_is_hoisted() {
+    assert_eq!(INLINE_NAME, "host");
+}
@@ -0,0 +1,76 @@
+//! Checks that a #[cfg] on the module is combined with the cfg selecting each target.
+// each enabled module selects the same shared fixture.
+#![allow(clippy::duplicate_mod)]
+
+use platform_spi::{platform_spi, platform_spi_module};
+
+// rustc evaluates the cfg before the macro runs, so an enabled module expands as usual.
+#[platform_spi(
+    module_path = "common",
+    family_targets = [host = [unix, windows]],
+    unsupported = false,
+    emit_target_list = true,
+    emit_current_target = true,
+    emit_platform_enum = true
+)]
+#[cfg(test)]
+mod enabled {
+    pub const NAME: &str;
+}
+
+#[test]
+fn enabled_module_is_selected() {
+    assert_eq!(NAME, "host");
+}
+
+// the function-like form passes the module's cfg to the macro, which gates everything it
+// generates. Nothing naming the missing type is compiled, and the generated items would
+// collide with those of the enabled module if they were not gated.
+platform_spi_module! {
+    module_path = "common",
+    family_targets = [host = [unix, windows]],
+    unsupported = false,
+    reexport_all = true,
+    emit_target_list = true,
+    emit_current_target = true,
+    emit_platform_enum = true;
+
+    #[cfg(not(test))]
+    mod disabled {
+        pub const NAME: &str;
+        pub type Missing = MissingImpl;
+        impl Copy for Missing {}
+        assert_size!(Missing == 4096);
+    }
+}
+
+#[test]
+fn generated_items_follow_the_enabled_module() {
+    assert_eq!(SUPPORTED_TARGETS, &["unix", "windows"]);
+    assert_ne!(current_target(), "unknown");
+    assert_ne!(PLATFORM, Platform::Unsupported);
+}
+
+// the Default assertion is gated by both the module's cfg and the alias's.
+platform_spi_module! {
+    module_path = "common",
+    family_targets = [host = [unix, windows]],
+    unsupported = false,
+    default_constructors = true;
+
+    #[cfg(test)]
+    mod constructed {
+        pub use NAME as CONSTRUCTED_NAME;
+
+        #[cfg(not(miri))]
+        pub type Counter = u32;
+
+        impl Default for Counter {}
+    }
+}
+
+#[test]
+fn constructor_is_generated_under_both_cfgs() {
+    assert_eq!(new_counter(), 0);
+    assert_eq!(CONSTRUCTED_NAME, "host");
+}
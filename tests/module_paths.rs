@@ -0,0 +1,77 @@
+//! Checks that module_path is joined to each source file name with a single separator,
+//! and without "." segments.
+// each spelling of the path resolves to the same shared fixture, loaded once per module.
+#![allow(clippy::duplicate_mod)]
+
+use platform_spi::platform_spi;
+
+// module files of a #[path] module are resolved from its own directory, so "." is "common".
+#[path = "common/dot.rs"]
+mod dot;
+
+#[path = "common/default.rs"]
+mod default;
+
+#[platform_spi(module_path = "common", family_targets = [host = [unix, windows]], unsupported = false)]
+mod dir {
+    pub use NAME as DIR_NAME;
+}
+
+#[platform_spi(module_path = "common/", family_targets = [host = [unix, windows]], unsupported = false)]
+mod trailing_slash {
+    pub use NAME as TRAILING_SLASH_NAME;
+}
+
+#[platform_spi(module_path = "./common/./", family_targets = [host = [unix, windows]], unsupported = false)]
+mod dotted {
+    pub use NAME as DOTTED_NAME;
+}
+
+#[platform_spi(module_path = "../tests/common", family_targets = [host = [unix, windows]], unsupported = false)]
+mod nested_directory {
+    pub use NAME as NESTED_DIRECTORY_NAME;
+}
+
+#[test]
+fn default_module_path() {
+    assert_eq!(default::DEFAULT_NAME, "host");
+}
+
+#[test]
+fn dotted_module_path() {
+    assert_eq!(DOTTED_NAME, "host");
+}
+
+#[test]
+fn nested_directory_module_path() {
+    assert_eq!(NESTED_DIRECTORY_NAME, "host");
+}
+
+#[test]
+fn dot_module_path() {
+    assert_eq!(dot::DOT_NAME, "host");
+}
+
+#[test]
+fn dir_module_path() {
+    assert_eq!(DIR_NAME, "host");
+}
+
+#[test]
+fn trailing_slash_module_path() {
+    assert_eq!(TRAILING_SLASH_NAME, "host");
+}
+
+mod nested {
+    use platform_spi::platform_spi;
+
+    #[platform_spi(module_path = "common", family_targets = [host = [unix, windows]], unsupported = false)]
+    mod platform {
+        pub use NAME as NESTED_NAME;
+    }
+}
+
+#[test]
+fn module_path_in_inline_module() {
+    assert_eq!(nested::NESTED_NAME, "host");
+}
@@ -0,0 +1,40 @@
+//! Checks that a full contract builds in a no_std crate.
+#![no_std]
+
+use platform_spi::platform_spi;
+
+#[platform_spi(
+    module_path = "no_std", 
+    family_targets = [host = [unix, windows]], 
+    unsupported = false,
+    require_send_sync = [PlatformDevice]
+)]
+mod platform {
+    pub type PlatformDevice = DeviceImpl;
+
+    pub use DeviceError;
+
+    pub const MAX_DEVICES: usize;
+
+    pub static DEFAULT_DEVICE: PlatformDevice;
+
+    impl Default for PlatformDevice {}
+
+    impl !Copy for PlatformDevice {}
+
+    pub trait Device {}
+
+    pub type DeviceAlias = DeviceImpl;
+
+    assert_impl_any!(PlatformDevice: Clone, Default);
+    assert_type_eq!(PlatformDevice == DeviceAlias);
+    assert_size!(PlatformDevice == 4);
+    assert_obj_safe!(Device);
+}
+
+#[test]
+fn contract_builds_without_std() {
+    assert_eq!(MAX_DEVICES, 2);
+    assert_eq!(DEFAULT_DEVICE.id, PlatformDevice::default().id);
+    assert_eq!(DeviceError::NAME, "device");
+}
@@ -0,0 +1,15 @@
+#[derive(Default)]
+pub struct DeviceImpl {
+    pub id: u32,
+}
+
+pub struct DeviceError;
+impl DeviceError {
+    pub const NAME: &'static str = "device";
+}
+
+pub const MAX_DEVICES: usize = 2;
+
+pub static DEFAULT_DEVICE: DeviceImpl = DeviceImpl { id: 0 };
+
+pub trait Device {}
@@ -0,0 +1,46 @@
+//! Checks that allow_shared_items emits code shared by every platform alongside the contract.
+
+use platform_spi::platform_spi;
+
+#[platform_spi(module_path = "common", family_targets = [host = [unix, windows]], unsupported = false, allow_shared_items = true)]
+mod platform {
+    pub use NAME as PLATFORM_NAME;
+
+    pub const LIMIT: u8 = 3;
+
+    pub fn describe() -> String {
+        format!("{PLATFORM_NAME} with limit {LIMIT}")
+    }
+
+    pub struct Shared;
+
+    impl Shared {
+        pub fn limit(&self) -> u8 {
+            LIMIT
+        }
+    }
+
+    // an empty inherent impl is never a contract marker, so it is shared as written.
+    impl Shared {}
+
+    pub trait Helper {
+        fn helper(&self) -> u8;
+    }
+
+    impl Helper for Shared {
+        fn helper(&self) -> u8 {
+            self.limit() + 1
+        }
+    }
+}
+
+#[test]
+fn shared_items_are_emitted_beside_the_contract() {
+    assert_eq!(describe(), "host with limit 3");
+    assert_eq!(Shared.limit(), LIMIT);
+}
+
+#[test]
+fn shared_trait_with_items_is_emitted() {
+    assert_eq!(Shared.helper(), 4);
+}
@@ -0,0 +1,58 @@
+//! Checks that source files are named by the file_pattern, extension, and single arguments.
+// each invocation selects a file of the shared fixture directory, loaded once per module.
+#![allow(clippy::duplicate_mod)]
+
+use platform_spi::platform_spi;
+
+// the target names the directory, so "{target}/host.rs" selects "common/host.rs".
+#[platform_spi(file_pattern = "{target}/host.rs", family_targets = [common = [unix, windows]], unsupported = false)]
+mod patterned {
+    pub use NAME as PATTERNED_NAME;
+}
+
+#[platform_spi(module_path = "common", extension = "gen.rs", family_targets = [host = [unix, windows]], unsupported = false)]
+mod generated {
+    pub use NAME as GENERATED_NAME;
+}
+
+#[platform_spi(module_path = "common", single = "host.rs")]
+mod single {
+    pub use NAME as SINGLE_NAME;
+}
+
+// redox never matches, so the default fallback file is selected from the module directory.
+#[platform_spi(module_path = "common", targets = [redox])]
+mod fallback {
+    pub use NAME as FALLBACK_NAME;
+}
+
+// the default fallback file takes the extension too, as "common/unsupported.gen.rs".
+#[platform_spi(module_path = "common", extension = "gen.rs", targets = [redox])]
+mod generated_fallback {
+    pub use NAME as GENERATED_FALLBACK_NAME;
+}
+
+#[test]
+fn file_pattern_names_each_file() {
+    assert_eq!(PATTERNED_NAME, "host");
+}
+
+#[test]
+fn extension_replaces_rs() {
+    assert_eq!(GENERATED_NAME, "generated");
+}
+
+#[test]
+fn single_file_is_imported_everywhere() {
+    assert_eq!(SINGLE_NAME, "host");
+}
+
+#[test]
+fn default_fallback_is_found_in_module_path() {
+    assert_eq!(FALLBACK_NAME, "unsupported");
+}
+
+#[test]
+fn default_fallback_takes_the_extension() {
+    assert_eq!(GENERATED_FALLBACK_NAME, "unsupported generated");
+}
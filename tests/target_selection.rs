@@ -0,0 +1,75 @@
+//! Checks that each way of listing targets selects the matching source file.
+// each invocation selects the same shared fixture, loaded once per module.
+#![allow(clippy::duplicate_mod)]
+
+use platform_spi::platform_spi;
+
+#[platform_spi(module_path = "common", groups = { host = [unix, windows] }, family_targets = [host], unsupported = false)]
+mod grouped {
+    pub use NAME as GROUPED_NAME;
+}
+
+#[platform_spi(
+    module_path = "common",
+    targets = [
+        { family = unix, pointer_width = 64 } => "host.rs",
+        { family = unix, pointer_width = 32 } => "host.rs",
+        { family = windows } => "host.rs"
+    ],
+    unsupported = false
+)]
+mod matrix {
+    pub use NAME as MATRIX_NAME;
+}
+
+#[platform_spi(module_path = "common", targets = [host = any(unix, windows)], unsupported = false)]
+mod predicate {
+    pub use NAME as PREDICATE_NAME;
+}
+
+// the "redox" file does not exist, so building fails if its doc arm is taken outside rustdoc.
+#[platform_spi(module_path = "common", targets = [redox], family_targets = [host = [unix, windows]], doc_target = redox, unsupported = false)]
+mod documented {
+    pub use NAME as DOCUMENTED_NAME;
+}
+
+#[platform_spi(module_path = "common", strict_targets = true, targets = [redox], default = "host.rs")]
+mod strict {
+    pub use NAME as STRICT_NAME;
+}
+
+// the error is emitted only where no target matches, so a supported build is unaffected.
+#[platform_spi(module_path = "common", family_targets = [host = [unix, windows]], unsupported = error("only unix and windows are supported"))]
+mod erroring {
+    pub use NAME as ERRORING_NAME;
+}
+
+#[test]
+fn group_is_listed_by_name() {
+    assert_eq!(GROUPED_NAME, "host");
+}
+
+#[test]
+fn matrix_target_combines_keys() {
+    assert_eq!(MATRIX_NAME, "host");
+}
+
+#[test]
+fn predicate_target_names_its_file() {
+    assert_eq!(PREDICATE_NAME, "host");
+}
+
+#[test]
+fn doc_target_is_only_selected_for_rustdoc() {
+    assert_eq!(DOCUMENTED_NAME, "host");
+}
+
+#[test]
+fn strict_targets_accept_known_os_names() {
+    assert_eq!(STRICT_NAME, "host");
+}
+
+#[test]
+fn unsupported_error_spares_supported_targets() {
+    assert_eq!(ERRORING_NAME, "host");
+}
@@ -0,0 +1,24 @@
+//! Checks that hoisted aliases keep the visibility written in the contract.
+// widening a restricted alias to `pub` inside the private `api` module would trip this lint.
+#![deny(unreachable_pub)]
+// hoisted re-exports may go unused, but must not warn when they do.
+#![deny(unused_imports)]
+
+#[path = "visibility/api.rs"]
+mod api;
+
+#[test]
+fn crate_use_alias_is_visible_in_crate() {
+    assert_eq!(api::CrateVisible::NAME, "crate");
+}
+
+#[test]
+fn super_use_alias_is_visible_in_parent() {
+    assert_eq!(api::SuperVisible::NAME, "super");
+}
+
+#[test]
+fn crate_module_is_visible_in_crate() {
+    assert_eq!(api::crate_platform::NAME, api::NAME);
+    assert_eq!(api::NAME, "host");
+}
@@ -0,0 +1,31 @@
+use platform_spi::platform_spi;
+
+#[platform_spi(family_targets = [host = [unix, windows]], unsupported = false)]
+mod platform {
+    pub(crate) use CrateImpl as CrateVisible;
+    pub(super) use SuperImpl as SuperVisible;
+    pub(in crate::api) use RestrictedImpl as RestrictedVisible;
+    pub(in crate::api) type RestrictedType = RestrictedImpl;
+
+    // restricted re-exports the crate never uses, which must not warn.
+    pub(crate) fn unused_fn() -> u8;
+    pub(crate) static UNUSED_STATIC: u8;
+    pub(crate) trait UnusedTrait {}
+}
+
+#[test]
+fn restricted_use_alias_is_visible_in_path() {
+    assert_eq!(RestrictedVisible::NAME, "restricted");
+}
+
+#[test]
+fn restricted_type_alias_is_visible_in_path() {
+    assert_eq!(RestrictedType::NAME, "restricted");
+}
+
+// the module keeps its restricted visibility, so its items are reachable from the crate root.
+#[platform_spi(module_path = "../common", family_targets = [host = [unix, windows]], unsupported = false)]
+#[allow(unreachable_pub)]
+pub(crate) mod crate_platform {
+    pub(crate) const NAME: &str;
+}
@@ -0,0 +1,25 @@
+pub(crate) struct CrateImpl;
+impl CrateImpl {
+    pub(crate) const NAME: &'static str = "crate";
+}
+
+pub(crate) struct SuperImpl;
+impl SuperImpl {
+    pub(crate) const NAME: &'static str = "super";
+}
+
+pub(crate) struct RestrictedImpl;
+impl RestrictedImpl {
+    pub(crate) const NAME: &'static str = "restricted";
+}
+
+pub(crate) fn unused_fn() -> u8 {
+    <CrateImpl as UnusedTrait>::ID
+}
+
+pub(crate) static UNUSED_STATIC: u8 = 0;
+
+pub(crate) trait UnusedTrait {
+    const ID: u8 = 0;
+}
+impl UnusedTrait for CrateImpl {}